@@ -0,0 +1,270 @@
+//! Certificates that let a claim of unsolvability be checked independently of the search that made it.
+//! 
+//! A certificate is the set of every position reachable from a deal. Checking it only needs the move generator:
+//! the deal's position must be in the set, no position in the set may be won, and every move from every position
+//! must land back inside the set. If all that holds, no sequence of moves can reach a win.
+use std::{collections::HashSet, fmt};
+use crate::state::*;
+
+/// Every position reachable from some deal, kept sorted so the encoding can share prefixes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    states: Vec<BoardState>,
+}
+
+impl FromIterator<BoardState> for Certificate {
+    fn from_iter<I: IntoIterator<Item = BoardState>>(iter: I) -> Self {
+        let mut states: Vec<BoardState> = iter.into_iter().collect();
+        states.sort();
+        states.dedup();
+        Self { states }
+    }
+}
+
+/// Why a certificate doesn't prove a deal unsolvable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertError {
+    /// The bytes aren't a certificate.
+    Malformed,
+    /// The deal's own starting position isn't listed.
+    MissingStart,
+    /// A listed position is already won.
+    Won(BoardState),
+    /// A move from a listed position reaches one that isn't listed.
+    NotClosed { from: BoardState, mv: Move },
+}
+
+impl fmt::Display for CertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "not a valid certificate"),
+            Self::MissingStart => write!(f, "certificate doesn't include the deal's starting position"),
+            Self::Won(_) => write!(f, "certificate includes a won position"),
+            Self::NotClosed { mv, .. } => write!(f, "certificate is missing the position after {:?} from a listed one", mv),
+        }
+    }
+}
+
+impl std::error::Error for CertError {}
+
+const MAGIC: &[u8; 8] = b"FFCERT01";
+const STATE_LEN: usize = CARDS_COUNT as usize;
+
+impl Certificate {
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn states(&self) -> &[BoardState] {
+        &self.states
+    }
+
+    /// Encode as a header and count, then each state as the length of the prefix it shares with the one before,
+    /// followed by the rest of its bytes. Sorted states share long prefixes, which keeps this compact.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&(self.states.len() as u64).to_le_bytes());
+
+        let mut prev = [0u8; STATE_LEN];
+        for (i, state) in self.states.iter().enumerate() {
+            let bytes = state.cards.map(|c| c.0);
+            let shared = if i == 0 { 0 } else { bytes.iter().zip(prev.iter()).take_while(|(a, b)| a == b).count() };
+            out.push(shared as u8);
+            out.extend_from_slice(&bytes[shared..]);
+            prev = bytes;
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CertError> {
+        let rest = bytes.strip_prefix(MAGIC).ok_or(CertError::Malformed)?;
+        let (count, mut rest) = rest.split_first_chunk::<8>().ok_or(CertError::Malformed)?;
+        let count = u64::from_le_bytes(*count) as usize;
+
+        let mut states = Vec::with_capacity(count.min(rest.len()));
+        let mut prev = [0u8; STATE_LEN];
+        for _ in 0..count {
+            let (&shared, tail) = rest.split_first().ok_or(CertError::Malformed)?;
+            let shared = shared as usize;
+            if shared > STATE_LEN || tail.len() < STATE_LEN - shared { return Err(CertError::Malformed) }
+            prev[shared..].copy_from_slice(&tail[..STATE_LEN - shared]);
+            rest = &tail[STATE_LEN - shared..];
+
+            let state = BoardState { cards: prev.map(C) };
+            if !well_formed(&state) { return Err(CertError::Malformed) }
+            states.push(state);
+        }
+        if !rest.is_empty() { return Err(CertError::Malformed) }
+
+        Ok(Self { states })
+    }
+}
+
+/// Whether `state` is a position cards can actually be in, which [`Board::from`] relies on: every card placed, at
+/// most one in the freecell, the rest stacked in at most 11 columns without forks or loops, and each foundation a run
+/// up from its base, or for the major arcana down from the World as well.
+fn well_formed(state: &BoardState) -> bool {
+    let cards = &state.cards;
+    if cards.iter().any(|at| at.0 > EVERY_HIGH) { return false }
+    let mut covered = [false; STATE_LEN];
+    let (mut columns, mut freecell) = (0, 0);
+    for (i, at) in cards.iter().enumerate() {
+        match at.info() {
+            CardInfo::Card(..) => if std::mem::replace(&mut covered[at.0 as usize], true) { return false },
+            CardInfo::Tableau => columns += 1,
+            CardInfo::Freecell => freecell += 1,
+            CardInfo::Foundation => {},
+            CardInfo::DownFoundn => if C(i as u8).info().card_suit().is_minor() { return false },
+            CardInfo::NoCard => return false,
+        }
+    }
+    if columns > 11 || freecell > 1 { return false }
+
+    // every stack must come down to the table; a loop never gets there
+    for &at in cards {
+        let mut below = at;
+        for _ in 0..=STATE_LEN {
+            if !below.info().is_card() { break }
+            below = cards[below.0 as usize];
+        }
+        if at.info().is_card() && below != C::TABLEAU { return false }
+    }
+
+    fn run_from(cards: &[C], suit: impl Iterator<Item = u8>, pile: C) -> bool {
+        suit.skip_while(|&c| cards[c as usize] == pile).all(|c| cards[c as usize] != pile)
+    }
+    [WANDS_BASE..=WANDS_HIGH, STARS_BASE..=STARS_HIGH, SWRDS_BASE..=SWRDS_HIGH, CUUPS_BASE..=CUUPS_HIGH, MAGIC_BASE..=MAGIC_HIGH]
+        .into_iter().all(|suit| run_from(cards, suit, C::FOUNDATION))
+        && run_from(cards, (MAGIC_BASE..=MAGIC_HIGH).rev(), C::DOWNFOUNDN)
+}
+
+/// Check that `cert` proves `start` unsolvable, re-expanding every listed position from scratch.
+pub fn verify_unsolvable(start: Board, cert: &Certificate) -> Result<(), CertError> {
+    if !cert.states.iter().all(well_formed) { return Err(CertError::Malformed) }
+    let listed: HashSet<&BoardState> = cert.states.iter().collect();
+
+    let mut start = start;
+    start.apply_forced();
+    if !listed.contains(&start.state) { return Err(CertError::MissingStart) }
+
+    for state in &cert.states {
+        let board = Board::from(*state);
+        if board.is_won() { return Err(CertError::Won(*state)) }
+        for mv in &board.legal_moves() {
+            let mut next = board;
+            next.apply_move(*mv);
+            if !listed.contains(&next.state) {
+                return Err(CertError::NotClosed { from: *state, mv: *mv });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod certificate_tests {
+    use super::*;
+    use crate::deal::deal;
+    use crate::solve::*;
+
+    /// A fresh deal that the exhaustive search finds no way to win.
+    const UNSOLVABLE_SEED: u64 = 2;
+
+    fn certify(board: Board) -> Certificate {
+        match solve_certified(board) {
+            (SolveResult::Unsolvable { states_explored }, Some(cert)) => {
+                assert_eq!(cert.len(), states_explored);
+                cert
+            },
+            other => panic!("expected a certificate: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn certificate_verifies() {
+        let board: Board = deal(UNSOLVABLE_SEED).into();
+        let cert = certify(board);
+        assert!(cert.len() > 1);
+        assert_eq!(verify_unsolvable(board, &cert), Ok(()));
+
+        // a different deal's positions don't cover this one
+        let other: Board = deal(UNSOLVABLE_SEED + 10).into();
+        assert_eq!(verify_unsolvable(other, &cert), Err(CertError::MissingStart));
+    }
+
+    #[test]
+    fn certificate_roundtrip() {
+        let cert = certify(deal(UNSOLVABLE_SEED).into());
+        let bytes = cert.to_bytes();
+        assert!(bytes.len() < cert.len() * STATE_LEN / 2, "poor compression: {} bytes", bytes.len());
+        assert_eq!(Certificate::from_bytes(&bytes), Ok(cert));
+
+        assert_eq!(Certificate::from_bytes(&bytes[..bytes.len() - 1]), Err(CertError::Malformed));
+        assert_eq!(Certificate::from_bytes(b"not a certificate"), Err(CertError::Malformed));
+    }
+
+    #[test]
+    fn tampered_certificate_fails() {
+        let board: Board = deal(UNSOLVABLE_SEED).into();
+        let cert = certify(board);
+        let mut start = board;
+        start.apply_forced();
+
+        let missing = cert.states().iter().find(|s| **s != start.state).unwrap();
+        let holed: Certificate = cert.states().iter().filter(|s| *s != missing).copied().collect();
+        assert!(matches!(verify_unsolvable(board, &holed), Err(CertError::NotClosed { .. })));
+
+        let won: Certificate = cert.states().iter().copied().chain([BoardState::default()]).collect();
+        assert_eq!(verify_unsolvable(board, &won), Err(CertError::Won(BoardState::default())));
+    }
+
+    #[test]
+    fn malformed_states_rejected() {
+        let with = |placed: &[(u8, C)]| {
+            let mut state = BoardState::default();
+            for &(card, at) in placed {
+                state.cards[card as usize] = at;
+            }
+            state
+        };
+        let (wk, sk, ck) = (WANDS_HIGH, STARS_HIGH, CUUPS_HIGH);
+        // a king in the freecell and a queen on another in a column are fine
+        assert!(well_formed(&with(&[(wk, C::FREECELL), (sk, C::TABLEAU), (ck - 1, C::TABLEAU), (ck, C(ck - 1))])));
+        assert!(well_formed(&with(&[(MAGIC_HIGH, C::DOWNFOUNDN), (MAGIC_HIGH - 1, C::TABLEAU)])));
+
+        let bad = [
+            with(&[(wk, C::NO_CARD)]),
+            with(&[(wk, C::FREECELL), (sk, C::FREECELL)]),
+            with(&[(wk, C::TABLEAU), (sk, C(wk)), (ck, C(wk))]),
+            with(&[(wk, C(sk)), (sk, C(wk))]),
+            with(&[(wk, C(wk))]),
+            with(&[(wk, C::FREECELL), (sk, C(wk))]),
+            with(&[(WANDS_BASE, C::TABLEAU)]),
+            with(&[(wk, C::DOWNFOUNDN)]),
+            with(&[(MAGIC_HIGH, C::TABLEAU), (MAGIC_HIGH - 1, C::DOWNFOUNDN)]),
+            with(&(WANDS_BASE..WANDS_BASE + 12).map(|c| (c, C::TABLEAU)).collect::<Vec<_>>()),
+        ];
+        for state in bad {
+            assert!(!well_formed(&state), "{:?}", state);
+            let cert = Certificate { states: vec![state] };
+            assert_eq!(Certificate::from_bytes(&cert.to_bytes()), Err(CertError::Malformed), "{:?}", state);
+            assert_eq!(verify_unsolvable(Board::default(), &cert), Err(CertError::Malformed), "{:?}", state);
+        }
+
+        let mut bytes = Certificate { states: vec![BoardState::default()] }.to_bytes();
+        *bytes.last_mut().unwrap() = EVERY_HIGH + 1;
+        assert_eq!(Certificate::from_bytes(&bytes), Err(CertError::Malformed));
+    }
+
+    #[test]
+    fn no_certificate_when_solved() {
+        assert_eq!(solve_certified(Board::default()), (SolveResult::Solved(vec![]), None));
+    }
+}
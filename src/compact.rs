@@ -0,0 +1,312 @@
+//! A packed form of [`BoardState`] for visited sets and storage.
+//!
+//! The state is written as one number in a mixed radix, each digit counting the choices left given the digits
+//! before it: how far each foundation has been built, which card (if any) is in the freecell, which cards sit at
+//! the bottom of a column, how long each column is, and the order of the cards above the bottoms. Columns are
+//! taken in order of their bottom card, the same way [`BoardState`] doesn't distinguish them, so the packing is
+//! canonical and two states are equal exactly when their packed forms are. The largest value needs under 381 bits.
+use crate::state::*;
+
+const WORDS: usize = 6;
+const ALL_CARDS: u128 = (1 << CARDS_COUNT) - 1;
+
+/// A [`BoardState`] packed losslessly into 48 bytes.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompactState([u64; WORDS]);
+
+/// `BINOM[n][k]` is n choose k, for as many cards as are in play and as many columns as there are.
+const BINOM: [[u64; 12]; CARDS_COUNT as usize + 1] = {
+    let mut table = [[0; 12]; CARDS_COUNT as usize + 1];
+    let mut n = 0;
+    while n < table.len() {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k < 12 && n > 0 {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+};
+
+/// Builds the packed number a digit at a time, least significant first.
+struct Writer {
+    value: [u64; WORDS],
+    place: [u64; WORDS],
+}
+
+impl Writer {
+    fn new() -> Self {
+        let mut place = [0; WORDS];
+        place[0] = 1;
+        Self { value: [0; WORDS], place }
+    }
+
+    fn push(&mut self, digit: u64, radix: u64) {
+        debug_assert!(digit < radix, "digit {} out of range for radix {}", digit, radix);
+        let mut carry = 0;
+        for (v, p) in self.value.iter_mut().zip(self.place) {
+            let sum = *v as u128 + p as u128 * digit as u128 + carry;
+            *v = sum as u64;
+            carry = sum >> 64;
+        }
+        debug_assert_eq!(carry, 0, "compact state overflowed");
+        let mut carry = 0;
+        for p in self.place.iter_mut() {
+            let prod = *p as u128 * radix as u128 + carry;
+            *p = prod as u64;
+            carry = prod >> 64;
+        }
+        debug_assert_eq!(carry, 0, "compact state overflowed");
+    }
+}
+
+/// Takes digits back off the packed number in the order they were pushed.
+struct Reader([u64; WORDS]);
+
+impl Reader {
+    fn pop(&mut self, radix: u64) -> u64 {
+        let mut rem = 0u128;
+        for w in self.0.iter_mut().rev() {
+            let cur = (rem << 64) | *w as u128;
+            *w = (cur / radix as u128) as u64;
+            rem = cur % radix as u128;
+        }
+        rem as u64
+    }
+}
+
+/// Position of `card` among the cards in `set`, counting up from the lowest.
+fn rank_in(set: u128, card: u8) -> u64 {
+    (set & ((1 << card) - 1)).count_ones() as u64
+}
+
+/// The card at position `index` among the cards in `set`.
+fn nth_in(mut set: u128, index: u64) -> u8 {
+    for _ in 0..index {
+        set &= set - 1;
+    }
+    set.trailing_zeros() as u8
+}
+
+/// Write a `k`-subset of `0..n`, given in increasing order, as its index among all such subsets.
+fn push_subset(w: &mut Writer, n: usize, members: impl Iterator<Item = u64>) {
+    let mut k = 0;
+    let mut rank = 0;
+    for m in members {
+        k += 1;
+        rank += BINOM[m as usize][k];
+    }
+    w.push(rank, BINOM[n][k]);
+}
+
+/// Read back a `k`-subset of `0..n` written by [`push_subset`], in decreasing order.
+fn pop_subset(r: &mut Reader, n: usize, k: usize) -> impl Iterator<Item = u64> {
+    let mut rank = r.pop(BINOM[n][k]);
+    let mut top = n;
+    (1..=k).rev().map(move |i| {
+        top -= 1;
+        while BINOM[top][i] > rank {
+            top -= 1;
+        }
+        rank -= BINOM[top][i];
+        top as u64
+    })
+}
+
+const MINORS: [(u8, u8); 4] = [(WANDS_BASE, WANDS_COUNT), (STARS_BASE, STARS_COUNT), (SWRDS_BASE, SWRDS_COUNT), (CUUPS_BASE, CUUPS_COUNT)];
+
+impl From<BoardState> for CompactState {
+    fn from(state: BoardState) -> Self {
+        let mut w = Writer::new();
+        let where_is = |card: u8| state.cards[card as usize];
+        let count = |base: u8, len: u8, at: C| (base..base + len).filter(|&c| where_is(c) == at).count() as u8;
+
+        // foundations, which are always a run up from the bottom of the suit (or down from the top, for the World)
+        let mut rest = ALL_CARDS;
+        for (base, len) in MINORS {
+            let n = count(base, len, C::FOUNDATION);
+            w.push(n as u64, len as u64 + 1);
+            rest &= !(((1 << n) - 1) << base);
+        }
+        let up = count(MAGIC_BASE, MAGIC_COUNT, C::FOUNDATION);
+        let down = count(MAGIC_BASE, MAGIC_COUNT, C::DOWNFOUNDN);
+        w.push(up as u64, MAGIC_COUNT as u64 + 1);
+        w.push(down as u64, (MAGIC_COUNT - up) as u64 + 1);
+        rest &= !(((1 << up) - 1) << MAGIC_BASE);
+        rest &= !(((1 << down) - 1) << (MAGIC_HIGH + 1 - down));
+        debug_assert!((0..CARDS_COUNT).all(|c| (rest >> c & 1 == 0) == matches!(where_is(c).info(), CardInfo::Foundation | CardInfo::DownFoundn)),
+            "foundations aren't runs");
+
+        let radix = rest.count_ones() as u64 + 1;
+        match (0..CARDS_COUNT).find(|&c| where_is(c) == C::FREECELL) {
+            Some(card) => {
+                w.push(rank_in(rest, card) + 1, radix);
+                rest &= !(1 << card);
+            },
+            None => w.push(0, radix),
+        }
+
+        let n = rest.count_ones() as usize;
+        let bottoms: u128 = (0..CARDS_COUNT).filter(|&c| where_is(c) == C::TABLEAU).fold(0, |set, c| set | 1 << c);
+        let s = bottoms.count_ones() as usize;
+        w.push(s as u64, 12);
+        push_subset(&mut w, n, (0..CARDS_COUNT).filter(|&c| bottoms >> c & 1 == 1).map(|c| rank_in(rest, c)));
+
+        let mut above = [C::NO_CARD; CARDS_COUNT as usize];
+        for c in 0..CARDS_COUNT {
+            if where_is(c).info().is_card() {
+                above[where_is(c).0 as usize] = C(c);
+            }
+        }
+
+        // column heights above the bottom card, as the positions of the dividers between them in the run of tails
+        if s > 0 {
+            let mut height = 0;
+            let dividers = (0..CARDS_COUNT).filter(|&c| bottoms >> c & 1 == 1).enumerate().take(s - 1).map(|(j, bottom)| {
+                let mut card = above[bottom as usize];
+                while card != C::NO_CARD {
+                    height += 1;
+                    card = above[card.0 as usize];
+                }
+                height + j as u64
+            });
+            push_subset(&mut w, n - 1, dividers);
+        }
+
+        // the cards above the bottoms, column by column, each as its position among those not yet placed
+        let mut tails = rest & !bottoms;
+        for bottom in (0..CARDS_COUNT).filter(|&c| bottoms >> c & 1 == 1) {
+            let mut card = above[bottom as usize];
+            while card != C::NO_CARD {
+                w.push(rank_in(tails, card.0), tails.count_ones() as u64);
+                tails &= !(1 << card.0);
+                card = above[card.0 as usize];
+            }
+        }
+
+        CompactState(w.value)
+    }
+}
+
+impl From<CompactState> for BoardState {
+    fn from(packed: CompactState) -> Self {
+        let mut r = Reader(packed.0);
+        let mut state = BoardState { cards: [C::NO_CARD; CARDS_COUNT as usize] };
+
+        let mut rest = ALL_CARDS;
+        for (base, len) in MINORS {
+            let n = r.pop(len as u64 + 1) as u8;
+            for c in base..base + n {
+                state.cards[c as usize] = C::FOUNDATION;
+            }
+            rest &= !(((1 << n) - 1) << base);
+        }
+        let up = r.pop(MAGIC_COUNT as u64 + 1) as u8;
+        let down = r.pop((MAGIC_COUNT - up) as u64 + 1) as u8;
+        for c in MAGIC_BASE..MAGIC_BASE + up {
+            state.cards[c as usize] = C::FOUNDATION;
+        }
+        for c in MAGIC_HIGH + 1 - down..=MAGIC_HIGH {
+            state.cards[c as usize] = C::DOWNFOUNDN;
+        }
+        rest &= !(((1 << up) - 1) << MAGIC_BASE);
+        rest &= !(((1 << down) - 1) << (MAGIC_HIGH + 1 - down));
+
+        let freecell = r.pop(rest.count_ones() as u64 + 1);
+        if freecell > 0 {
+            let card = nth_in(rest, freecell - 1);
+            state.cards[card as usize] = C::FREECELL;
+            rest &= !(1 << card);
+        }
+
+        let n = rest.count_ones() as usize;
+        let s = r.pop(12) as usize;
+        let bottoms: u128 = pop_subset(&mut r, n, s).fold(0, |set, i| set | 1 << nth_in(rest, i));
+
+        let mut heights = [0; 11];
+        if s > 0 {
+            // the dividers come back last first, each giving the total height of the columns before it
+            let mut total = (n - s) as u64;
+            for (j, d) in (0..s - 1).rev().zip(pop_subset(&mut r, n - 1, s - 1)) {
+                let before = d - j as u64;
+                heights[j + 1] = total - before;
+                total = before;
+            }
+            heights[0] = total;
+        }
+
+        let mut tails = rest & !bottoms;
+        for (j, bottom) in (0..CARDS_COUNT).filter(|&c| bottoms >> c & 1 == 1).enumerate() {
+            state.cards[bottom as usize] = C::TABLEAU;
+            let mut under = C(bottom);
+            for _ in 0..heights[j] {
+                let card = nth_in(tails, r.pop(tails.count_ones() as u64));
+                state.cards[card as usize] = under;
+                tails &= !(1 << card);
+                under = C(card);
+            }
+        }
+
+        state
+    }
+}
+
+impl From<&Board> for CompactState {
+    fn from(board: &Board) -> Self {
+        board.state.into()
+    }
+}
+
+
+#[cfg(test)]
+mod compact_tests {
+    use super::*;
+    use crate::{deal::{deal, Rng}, text::fixtures::*};
+
+    #[test]
+    fn fits_target_size() {
+        assert_eq!(std::mem::size_of::<CompactState>(), 48);
+    }
+
+    #[test]
+    fn roundtrip_fixed_positions() {
+        let fixtures = [TWO_KINGS, CLEARED, MAJORS];
+        for s in fixtures {
+            let board = board(s);
+            assert_eq!(BoardState::from(CompactState::from(board.state)), board.state, "{}", s);
+        }
+        let won = BoardState::default();
+        assert_eq!(BoardState::from(CompactState::from(won)), won);
+    }
+
+    #[test]
+    fn roundtrip_random_play() {
+        let mut rng = Rng::new(17);
+        for seed in 0..40 {
+            let mut board: Board = deal(seed).into();
+            board.apply_forced();
+            for _ in 0..200 {
+                let packed = CompactState::from(&board);
+                assert_eq!(BoardState::from(packed), board.state, "seed {}", seed);
+
+                let moves = board.legal_moves();
+                if moves.is_empty() { break }
+                board.apply_move(moves[rng.below(moves.len() as u64) as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn equal_exactly_when_states_are() {
+        let mut board: Board = deal(3).into();
+        board.apply_forced();
+        let before = CompactState::from(&board);
+        let mv = board.legal_moves()[0];
+        let undo = board.apply_move(mv);
+        assert_ne!(CompactState::from(&board), before);
+        board.undo_move(undo);
+        assert_eq!(CompactState::from(&board), before);
+    }
+}
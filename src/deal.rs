@@ -0,0 +1,89 @@
+//! Random starting layouts, reproducible from a seed.
+//! 
+//! 
+use crate::state::*;
+use crate::text::BoardLayout;
+
+/// SplitMix64, chosen because it is tiny, fully specified, and gives the same stream on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..n`, rejecting the biased top end of the range.
+    pub fn below(&mut self, n: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone { return x % n }
+        }
+    }
+}
+
+/// Number of cards dealt to each non-empty column.
+pub const DEAL_HEIGHT: usize = 7;
+/// The column left empty at the start of a game.
+pub const DEAL_GAP: usize = 5;
+
+/// Shuffle the cards in play into the starting shape: ten columns of seven either side of an empty middle column,
+/// with the aces already on the foundations (implied, as they aren't in play) and the freecell empty.
+pub fn deal(seed: u64) -> BoardLayout {
+    let mut cards: Vec<C> = (CARDS_BASE..=CARDS_HIGH).map(C).collect();
+
+    // Fisher-Yates
+    let mut rng = Rng::new(seed);
+    for i in (1..cards.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        cards.swap(i, j);
+    }
+
+    let mut tableau: [Vec<C>; 11] = Default::default();
+    let columns = (0..11).filter(|i| *i != DEAL_GAP);
+    for (tab, chunk) in columns.zip(cards.chunks(DEAL_HEIGHT)) {
+        tableau[tab] = chunk.to_vec();
+    }
+
+    tableau.try_into().expect("every card dealt exactly once")
+}
+
+
+#[cfg(test)]
+mod deal_tests {
+    use super::*;
+
+    #[test]
+    fn deal_shape() {
+        for seed in 0..100 {
+            let bl = deal(seed);
+            for (i, tab) in bl.tableau.iter().enumerate() {
+                assert_eq!(tab.len(), if i == DEAL_GAP { 0 } else { DEAL_HEIGHT });
+            }
+            assert_eq!(bl.info.foundation, [C::NO_CARD; 5]);
+            assert_eq!(bl.info.down_foundn, C::NO_CARD);
+            assert_eq!(bl.info.freecell, C::FREECELL);
+            assert_eq!(bl.to_string().parse::<BoardLayout>(), Ok(bl));
+        }
+    }
+
+    #[test]
+    fn deal_reproducible() {
+        assert_eq!(deal(1), deal(1));
+        assert_ne!(deal(1), deal(2));
+
+        // pinned so that a seed means the same deal across platforms and releases
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(deal(0).tableau[0].iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" "), "!8 $6 *5 $3 !T |4 !Q");
+    }
+}
@@ -0,0 +1,151 @@
+//! Position scores used to guide the best-first searches.
+//! 
+//! 
+use crate::state::*;
+
+/// Build a table of which card each card would sit on when stacked toward `step`, with `end` past the last rank.
+const fn stacking_table(step: i8, end: C) -> [C; CARDS_COUNT as usize] {
+    let bases = [WANDS_BASE, STARS_BASE, SWRDS_BASE, CUUPS_BASE, MAGIC_BASE];
+    let highs = [WANDS_HIGH, STARS_HIGH, SWRDS_HIGH, CUUPS_HIGH, MAGIC_HIGH];
+    let mut table = [C::NO_CARD; CARDS_COUNT as usize];
+    let mut s = 0;
+    while s < bases.len() {
+        let mut i = bases[s];
+        while i <= highs[s] {
+            let next = i as i8 + step;
+            table[i as usize] = if next < bases[s] as i8 || next > highs[s] as i8 { end } else { C(next as u8) };
+            i += 1;
+        }
+        s += 1;
+    }
+    table
+}
+
+/// The card each card sits on in a descending run, down from the king or the World onto an empty tableau.
+pub const DOWN: [C; CARDS_COUNT as usize] = stacking_table(1, C::TABLEAU);
+/// The card each card sits on in an ascending run, up from the two or the Fool out of the foundation.
+pub const UP: [C; CARDS_COUNT as usize] = stacking_table(-1, C::FOUNDATION);
+
+/// Number of cards sitting in the spot they'd occupy in a same-suit run.
+pub fn stacking(state: &BoardState) -> u32 {
+    state.cards.iter().zip(DOWN.iter().zip(UP.iter()))
+        .map(|(s, (d, u))| (s == d) as u32 + (s == u) as u32)
+        .sum()
+}
+
+/// Number of cards scored to the foundations.
+pub fn foundation(info: &BoardInfo) -> u32 {
+    let mut total = 0;
+    for top in &info.foundation {
+        let ti = top.info();
+        if ti.is_card() {
+            let rank = ti.card_rank() as u32;
+            total += if ti.card_suit().is_minor() { rank - 1 } else { rank + 1 };
+        }
+    }
+    let di = info.down_foundn.info();
+    if di.is_card() {
+        total += 22 - di.card_rank() as u32;
+    }
+    total
+}
+
+/// Number of empty tableau stacks and freecells.
+pub fn emptiness(info: &BoardInfo) -> u32 {
+    info.tableau.iter().filter(|t| **t == C::TABLEAU).count() as u32 + (info.freecell == C::FREECELL) as u32
+}
+
+/// Combined desirability of a position, higher being closer to a win.
+pub fn score(board: &Board) -> i32 {
+    (8 * foundation(&board.info) + stacking(&board.state) + 2 * emptiness(&board.info)) as i32
+}
+
+/// Lower bound on the player moves still needed, counting each card that has to be moved at least once more.
+///
+/// A minor card sitting anywhere above a lower card of its suit can't be scored where it is, nor can a major
+/// card with both a lower and a higher major below it, nor a minor card in the freecell, which it blocks.
+/// Each move shifts only one card and can take at most one card off this count, so the bound is consistent.
+pub fn min_moves(board: &Board) -> u32 {
+    let mut total = 0;
+
+    let fi = board.info.freecell.info();
+    if fi.is_card() && fi.card_suit().is_minor() {
+        total += 1;
+    }
+
+    let mut stack = [C::NO_CARD; CARDS_COUNT as usize];
+    for top in &board.info.tableau {
+        let mut len = 0;
+        let mut card = *top;
+        while card.info().is_card() {
+            stack[len] = card;
+            len += 1;
+            card = board.state.cards[card.0 as usize];
+        }
+
+        let mut lowest = [u8::MAX; 5];
+        let mut highest = 0;
+        for card in stack[..len].iter().rev() {
+            let ci = card.info();
+            let (suit, rank) = (ci.card_suit() as usize, ci.card_rank());
+            let blocked = if ci.card_suit().is_minor() {
+                lowest[suit] < rank
+            } else {
+                lowest[suit] < rank && highest > rank
+            };
+            total += blocked as u32;
+            lowest[suit] = lowest[suit].min(rank);
+            if !ci.card_suit().is_minor() { highest = highest.max(rank) }
+        }
+    }
+
+    total
+}
+
+
+#[cfg(test)]
+mod heuristic_tests {
+    use super::*;
+
+    fn c(s: &str) -> C { s.parse().unwrap() }
+
+    #[test]
+    fn min_moves_counts_buried() {
+        use crate::text::fixtures::*;
+        assert_eq!(min_moves(&Board::default()), 0);
+
+        // K* doesn't count against Q|, but Q! and K! are both above J!, and likewise for cups
+        assert_eq!(min_moves(&board(NEARLY_WON)), 4);
+
+        // 05 is stuck between 03 and 09, but 07 and 08 could still go to the descending foundation
+        assert_eq!(min_moves(&board(MAJORS)), 1);
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(DOWN[c("Q*").0 as usize], c("K*"));
+        assert_eq!(DOWN[c("K*").0 as usize], C::TABLEAU);
+        assert_eq!(DOWN[c("21").0 as usize], C::TABLEAU);
+        assert_eq!(UP[c("3*").0 as usize], c("2*"));
+        assert_eq!(UP[c("2*").0 as usize], C::FOUNDATION);
+        assert_eq!(UP[c("00").0 as usize], C::FOUNDATION);
+        assert_eq!(UP[c("2|").0 as usize], C::FOUNDATION);
+    }
+
+    #[test]
+    fn won_scores() {
+        let board = Board::default();
+        assert_eq!(foundation(&board.info), CARDS_COUNT as u32);
+        assert_eq!(emptiness(&board.info), 12);
+    }
+
+    #[test]
+    fn foundation_counts_both_majors() {
+        let info = BoardInfo {
+            foundation: [C::NO_CARD, c("3*"), C::NO_CARD, C::NO_CARD, c("01")],
+            down_foundn: c("20"),
+            ..Default::default()
+        };
+        assert_eq!(foundation(&info), 2 + 2 + 2);
+    }
+}
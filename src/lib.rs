@@ -0,0 +1,12 @@
+pub mod state;
+pub mod text;
+pub mod certificate;
+pub mod compact;
+pub mod deal;
+pub mod heuristic;
+pub mod prune;
+pub mod shorten;
+pub mod solve;
+pub mod supermove;
+pub mod survey;
+pub mod verify;
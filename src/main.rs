@@ -1,11 +1,346 @@
-use state::Board;
+use std::{env, fs, io::{self, IsTerminal, Read, Write}, process::ExitCode, sync::Arc, thread, time::Duration};
+use fortune2::{certificate::*, heuristic::foundation, solve::*, shorten::shorten, state::*, supermove::compress, survey::*, verify::verify_transcript};
+use fortune2::text::{instructions, run_instructions, BoardLayout, ParseError, Transcript};
 
-mod state;
-mod text;
+const USAGE: &str = "\
+usage: fortune2 solve [OPTIONS] [FILE|-]
+       fortune2 survey [OPTIONS] FIRST..LAST
+       fortune2 verify-unsolvable DEAL CERT
+       fortune2 verify DEAL MOVES
 
-fn main() {
-    println!("Hello, world!");
+solve reads a deal in the 11-line board format from FILE, or stdin if FILE is - or omitted,
+and prints the moves that solve it. While it searches, a status line on stderr shows how
+the search is going, if stderr is a terminal.
 
-    let mut b = Board::default();
-    b.apply_forced();
+solve options:
+    --mode MODE          search to run: best (default), dfs, ida or optimal; ida needs
+                         --max-nodes or --timeout, as it may never finish otherwise
+    --timeout SECS       give up after SECS seconds
+    --max-nodes N        give up after expanding N positions
+    --max-memory MB      give up once the search is using MB megabytes
+    --certificate FILE   run the exhaustive dfs search, and if the deal is unsolvable
+                         write a certificate proving it to FILE
+    --save FILE          also write the solution to FILE in move notation, for verify
+    --shorten            cut detours out of the solution before printing or saving it
+    --runs               list runs of cards that move together as one step
+
+survey deals every seed from FIRST up to but not including LAST, solves each one with the
+best-first search, and writes a row per deal as it finishes.
+
+survey options:
+    --max-nodes N        give up on a deal after expanding N positions
+    --timeout SECS       give up on a deal after SECS seconds
+    --threads N          deals to solve at once (default: one per core)
+    --format FMT         csv (default) or jsonl
+
+verify replays MOVES from DEAL, checking each move is legal and that they win the game.
+MOVES is in move notation: one move per line, giving the card to move, then >, then the
+card to put it on, FF for the freecell or -- for an empty column, such as 7* > 8*.
+Blank lines and lines starting with # are ignored.
+
+verify-unsolvable checks that CERT, written by solve --certificate, proves DEAL unsolvable.
+
+exit status:
+    0  solved, or solution or certificate accepted
+    1  bad usage or unreadable input
+    2  proven unsolvable
+    3  gave up at a limit
+    4  solution or certificate rejected";
+
+const EXIT_SOLVED: u8 = 0;
+const EXIT_USAGE: u8 = 1;
+const EXIT_UNSOLVABLE: u8 = 2;
+const EXIT_LIMIT: u8 = 3;
+const EXIT_REJECTED: u8 = 4;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let code = match args.first().map(String::as_str) {
+        Some("solve") => cmd_solve(&args[1..]),
+        Some("survey") => cmd_survey(&args[1..]),
+        Some("verify-unsolvable") => cmd_verify_unsolvable(&args[1..]),
+        Some("verify") => cmd_verify(&args[1..]),
+        _ => usage(),
+    };
+    ExitCode::from(code)
+}
+
+fn usage() -> u8 {
+    eprintln!("{}", USAGE);
+    EXIT_USAGE
+}
+
+/// Complain about an option the subcommand doesn't take.
+fn unknown_option(arg: &str) -> u8 {
+    eprintln!("error: unknown option {}", arg);
+    usage()
+}
+
+/// Read a deal from a file, or from stdin for `-`.
+fn read_layout(path: &str) -> Result<BoardLayout, String> {
+    let text = if path == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map_err(|e| format!("reading stdin: {}", e))?;
+        text
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("reading {}: {}", path, e))?
+    };
+    let name = if path == "-" { "stdin" } else { path };
+    text.parse().map_err(|e| format!("{}: {}", name, e))
+}
+
+fn cmd_solve(args: &[String]) -> u8 {
+    let mut mode = "best".to_string();
+    let mut config = SolveConfig::default();
+    let mut path = "-".to_string();
+    let mut cert_path = None;
+    let mut save_path = None;
+    let mut runs = false;
+    let mut cut_detours = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mode" => match args.next() {
+                Some(m) => mode = m.clone(),
+                None => return usage(),
+            },
+            "--timeout" => match args.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(secs) => config.timeout = Some(Duration::from_secs_f64(secs)),
+                None => return usage(),
+            },
+            "--max-nodes" => match args.next().and_then(|s| s.parse().ok()) {
+                Some(n) => config.max_nodes = Some(n),
+                None => return usage(),
+            },
+            "--max-memory" => match args.next().and_then(|s| s.parse::<usize>().ok()) {
+                Some(mb) => config.max_memory_bytes = Some(mb << 20),
+                None => return usage(),
+            },
+            "--certificate" => match args.next() {
+                Some(p) => cert_path = Some(p.clone()),
+                None => return usage(),
+            },
+            "--save" => match args.next() {
+                Some(p) => save_path = Some(p.clone()),
+                None => return usage(),
+            },
+            "--shorten" => cut_detours = true,
+            "--runs" => runs = true,
+            "-h" | "--help" => return usage(),
+            opt if opt.starts_with('-') && opt != "-" => return unknown_option(opt),
+            _ => path = arg.clone(),
+        }
+    }
+
+    let solver: fn(Board, &SolveConfig) -> (SolveResult, SearchStats) = match mode.as_str() {
+        "best" => solve_best_first_with,
+        "dfs" => solve_with,
+        "ida" => |board, config| solve_ida_with(board, DEFAULT_TABLE_BYTES, config),
+        "optimal" => solve_optimal_with,
+        _ => return usage(),
+    };
+    if mode == "ida" && config.max_nodes.is_none() && config.timeout.is_none() {
+        eprintln!("error: --mode ida needs --max-nodes or --timeout");
+        return EXIT_USAGE;
+    }
+
+    let board: Board = match read_layout(&path) {
+        Ok(layout) => layout.into(),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return EXIT_USAGE;
+        },
+    };
+
+    // a certificate always takes the dfs search, whatever the mode
+    let optimal = mode == "optimal" && cert_path.is_none();
+    let status = io::stderr().is_terminal();
+    if status {
+        config.progress = Some(Arc::new(|stats: &SearchStats| eprint!("\r\x1b[K{}", status_line(stats))));
+    }
+    let (result, cert) = match cert_path {
+        Some(_) => solve_certified_with(board, &config),
+        None => (solver(board, &config).0, None),
+    };
+    if status {
+        eprint!("\r\x1b[K");
+    }
+
+    if let (Some(path), Some(cert)) = (cert_path, cert) {
+        if let Err(e) = fs::write(&path, cert.to_bytes()) {
+            eprintln!("error: writing {}: {}", path, e);
+            return EXIT_USAGE;
+        }
+    }
+
+    match result {
+        SolveResult::Solved(moves) => {
+            let moves = if cut_detours { shorten(&board, &moves) } else { moves };
+            if let Some(path) = save_path {
+                if let Err(e) = fs::write(&path, Transcript::record(board, &moves).to_string()) {
+                    eprintln!("error: writing {}: {}", path, e);
+                    return EXIT_USAGE;
+                }
+            }
+            let compressed = if runs { compress(board, &moves) } else { None };
+            let run_steps = compressed.as_ref().and_then(|compressed| run_instructions(board, &compressed.moves));
+            if compressed.is_some() && run_steps.is_none() {
+                eprintln!("warning: the run moves don't replay, listing single cards instead");
+            }
+            let listed_runs = run_steps.is_some();
+            let steps = run_steps.unwrap_or_else(|| instructions(board, &moves));
+            for (i, step) in steps.iter().enumerate() {
+                println!("{:>4}. {}", i + 1, step);
+            }
+            if let Some(compressed) = compressed.filter(|_| listed_runs) {
+                println!("{} moves, {} as single cards", compressed.moves.len(), compressed.raw_len);
+            }
+            if optimal {
+                println!("optimal: {} moves", moves.len());
+            }
+            EXIT_SOLVED
+        },
+        SolveResult::Unsolvable { states_explored } => {
+            println!("unsolvable: exhausted {} positions", states_explored);
+            EXIT_UNSOLVABLE
+        },
+        SolveResult::LimitReached { best_partial, stats } => {
+            // show how far the search got, as the furthest it scored
+            for (i, step) in instructions(board, &best_partial).iter().enumerate() {
+                println!("{:>4}. {}", i + 1, step);
+            }
+            let mut reached = board;
+            reached.apply_forced();
+            for mv in &best_partial {
+                reached.apply_move(*mv);
+            }
+            println!("gave up: hit a limit after {} positions, having scored at best {} of {} cards",
+                stats.nodes_expanded, foundation(&reached.info), CARDS_COUNT);
+            EXIT_LIMIT
+        },
+    }
+}
+
+/// A short count, such as 950, 12.3K or 4.5M.
+fn short_count(n: f64) -> String {
+    match n {
+        n if n >= 1e6 => format!("{:.1}M", n / 1e6),
+        n if n >= 1e4 => format!("{:.1}K", n / 1e3),
+        n => format!("{:.0}", n),
+    }
+}
+
+/// One line summing up a search in progress, short enough for a narrow terminal.
+fn status_line(stats: &SearchStats) -> String {
+    format!("{} nodes  {} gen  {} dup  depth {}  open {}  best {}  {}/s  {:.1}s",
+        short_count(stats.nodes_expanded as f64), short_count(stats.nodes_generated as f64),
+        short_count(stats.duplicates as f64), short_count(stats.max_depth as f64), short_count(stats.open as f64), stats.best_score,
+        short_count(stats.nodes_per_sec()), stats.elapsed.as_secs_f64())
+}
+
+fn cmd_survey(args: &[String]) -> u8 {
+    let mut config = SolveConfig::default();
+    let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut jsonl = false;
+    let mut seeds = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-nodes" => match args.next().and_then(|s| s.parse().ok()) {
+                Some(n) => config.max_nodes = Some(n),
+                None => return usage(),
+            },
+            "--timeout" => match args.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(secs) => config.timeout = Some(Duration::from_secs_f64(secs)),
+                None => return usage(),
+            },
+            "--threads" => match args.next().and_then(|s| s.parse().ok()) {
+                Some(n) => threads = n,
+                None => return usage(),
+            },
+            "--format" => match args.next().map(String::as_str) {
+                Some("csv") => jsonl = false,
+                Some("jsonl") => jsonl = true,
+                _ => return usage(),
+            },
+            "-h" | "--help" => return usage(),
+            opt if opt.starts_with('-') => return unknown_option(opt),
+            range => match range.split_once("..").and_then(|(a, b)| Some(a.parse().ok()?..b.parse().ok()?)) {
+                Some(r) => seeds = Some(r),
+                None => return usage(),
+            },
+        }
+    }
+    let Some(seeds) = seeds else { return usage() };
+
+    let mut out = io::stdout().lock();
+    if !jsonl {
+        let _ = writeln!(out, "{}", Row::CSV_HEADER);
+    }
+    survey(seeds, &config, threads, |row| {
+        let _ = writeln!(out, "{}", if jsonl { row.to_json() } else { row.to_csv() });
+    });
+    EXIT_SOLVED
+}
+
+fn cmd_verify_unsolvable(args: &[String]) -> u8 {
+    let [deal_path, cert_path] = args else { return usage() };
+
+    let board: Board = match read_layout(deal_path) {
+        Ok(layout) => layout.into(),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return EXIT_USAGE;
+        },
+    };
+    let cert = match fs::read(cert_path) {
+        Ok(bytes) => Certificate::from_bytes(&bytes),
+        Err(e) => {
+            eprintln!("error: reading {}: {}", cert_path, e);
+            return EXIT_USAGE;
+        },
+    };
+
+    match cert.and_then(|cert| verify_unsolvable(board, &cert).map(|_| cert.len())) {
+        Ok(n) => {
+            println!("verified: deal is unsolvable, closed over {} positions", n);
+            EXIT_SOLVED
+        },
+        Err(e) => {
+            println!("rejected: {}", e);
+            EXIT_REJECTED
+        },
+    }
+}
+
+fn cmd_verify(args: &[String]) -> u8 {
+    let [deal_path, moves_path] = args else { return usage() };
+
+    let layout = match read_layout(deal_path) {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return EXIT_USAGE;
+        },
+    };
+    let text = fs::read_to_string(moves_path).map_err(|e| e.to_string());
+    let transcript: Transcript = match text.and_then(|t| t.parse().map_err(|e: ParseError| e.to_string())) {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            eprintln!("error: {}: {}", moves_path, e);
+            return EXIT_USAGE;
+        },
+    };
+    match verify_transcript(&layout, &transcript) {
+        Ok(()) => {
+            println!("verified: {} moves win the game", transcript.0.len());
+            EXIT_SOLVED
+        },
+        Err(e) => {
+            println!("rejected: {}", e);
+            EXIT_REJECTED
+        },
+    }
 }
@@ -0,0 +1,120 @@
+//! Dominance pruning: player moves that provably reach nothing the search doesn't already have in hand.
+//!
+//! Each rule only throws away a move whose resulting position is identical to one the search reaches anyway, either
+//! the position it just came from or the result of a sibling move, so pruning never changes whether a board is
+//! solvable or how short its shortest solution is.
+//!
+//! A search with a full visited set would throw those positions away anyway, so there the pruning only saves
+//! generating them. It's IDA* with too small a table to remember them that expands far fewer positions for it.
+use crate::state::*;
+
+/// How many moves each pruning rule threw away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pruned {
+    /// Moves of a column's only card into an empty column, which leave the position as it was.
+    pub no_op: usize,
+    /// Moves into an empty column other than the first, which all reach the same position as the first.
+    pub symmetric: usize,
+    /// Moves putting back the card just moved onto what it came from, which return to the previous position.
+    pub reversal: usize,
+}
+
+impl Pruned {
+    pub fn total(&self) -> usize {
+        self.no_op + self.symmetric + self.reversal
+    }
+}
+
+/// The move that led to a position, remembered so that taking it straight back can be recognised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastMove {
+    card: C,
+    from: C,
+}
+
+impl LastMove {
+    /// The move `undo` takes back, or `None` if it scored any cards, since moving back then doesn't undo it.
+    pub fn after(undo: &Undo) -> Option<Self> {
+        if undo.scored().next().is_some() { return None }
+        Some(Self { card: undo.card(), from: undo.under() })
+    }
+}
+
+/// The legal moves from `board` that aren't pruned by one of the rules, counting each one that is into `pruned`.
+/// `last` is the move that reached `board`, if it's known and didn't score anything.
+pub fn useful_moves(board: &Board, last: Option<LastMove>, pruned: &mut Pruned) -> MoveList {
+    let first_empty = MoveLoc::all().find(|loc| board.top(*loc) == C::TABLEAU);
+    let mut moves = MoveList::default();
+    for mv in &board.legal_moves() {
+        let card = board.top(mv.src);
+        let onto = match mv.dst {
+            MoveLoc::Tableau(_) => board.top(mv.dst),
+            MoveLoc::Freecell => C::FREECELL,
+        };
+
+        if onto == C::TABLEAU && board.state.cards[card.0 as usize] == C::TABLEAU {
+            pruned.no_op += 1;
+        } else if last.is_some_and(|last| last.card == card && last.from == onto) {
+            pruned.reversal += 1;
+        } else if onto == C::TABLEAU && Some(mv.dst) != first_empty {
+            pruned.symmetric += 1;
+        } else {
+            moves.push(*mv);
+        }
+    }
+    moves
+}
+
+
+#[cfg(test)]
+mod prune_tests {
+    use super::*;
+    use crate::text::fixtures::*;
+
+    #[test]
+    fn empty_columns() {
+        let board = settled(OPEN_COLUMNS);
+        let mut pruned = Pruned::default();
+        let moves = useful_moves(&board, None, &mut pruned);
+
+        let to_empty: Vec<_> = moves.iter().filter(|mv| board.top(mv.dst) == C::TABLEAU).collect();
+        assert_eq!(to_empty, [
+            &Move { src: MoveLoc::Tableau(1), dst: MoveLoc::Tableau(3) },
+            &Move { src: MoveLoc::Tableau(2), dst: MoveLoc::Tableau(3) },
+        ]);
+        assert_eq!(pruned, Pruned { no_op: 8, symmetric: 14, reversal: 0 });
+        assert_eq!(moves.len() + pruned.total(), board.legal_moves().len());
+    }
+
+    #[test]
+    fn reversal() {
+        let mut board = settled("07\n09 10\n05 11 08 06\n\n\n\n\n\n\n\n\n");
+        let undo = board.apply_move(Move { src: MoveLoc::Tableau(1), dst: MoveLoc::Freecell });
+        let last = LastMove::after(&undo);
+        assert!(last.is_some());
+
+        let mut pruned = Pruned::default();
+        let moves = useful_moves(&board, last, &mut pruned);
+        assert_eq!(pruned.reversal, 1);
+        assert!(!moves.contains(&Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(1) }));
+        // moving it on to an empty column instead is still worth trying
+        assert!(moves.contains(&Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(3) }));
+
+        // a lone card put in the freecell would go back to any empty column
+        let mut board = settled("07\n09 10\n05 11 08 06\n\n\n\n\n\n\n\n\n");
+        let undo = board.apply_move(Move { src: MoveLoc::Tableau(0), dst: MoveLoc::Freecell });
+        let mut pruned = Pruned::default();
+        let moves = useful_moves(&board, LastMove::after(&undo), &mut pruned);
+        assert_eq!(pruned.reversal, 9);
+        assert!(moves.iter().all(|mv| mv.src != MoveLoc::Freecell || board.top(mv.dst) != C::TABLEAU));
+    }
+
+    #[test]
+    fn no_reversal_after_scoring() {
+        let mut board = settled(TWO_KINGS);
+        let undo = board.apply_move(Move { src: MoveLoc::Tableau(0), dst: MoveLoc::Freecell });
+        assert!(LastMove::after(&undo).is_some());
+        let undo = board.apply_move(Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(2) });
+        assert_eq!(LastMove::after(&undo), None);
+    }
+}
@@ -0,0 +1,232 @@
+//! Cutting detours out of a finished solution.
+//!
+//! The heuristic searches stop at the first win they find, which often parks a card and fetches it straight back, or
+//! wanders through a few positions before returning to one it had already reached. [`shorten`] removes those without
+//! searching. From each position along the solution it tries a handful of variants of the next few moves: none at all,
+//! any single legal move, the moves as given with one or two of them left out, and the moves with two neighbours
+//! swapped and perhaps one after them left out. Whenever a variant reaches a state the solution only gets to later,
+//! and in fewer moves, it's spliced in and the rest of the solution carries on from there, renumbered to match the
+//! columns.
+use std::collections::HashMap;
+use crate::state::*;
+
+/// How many of the following moves are replayed, less the ones left out, when looking for a shorter way through them.
+const WINDOW: usize = 16;
+
+/// Apply the forced moves and then `moves` from `start`, if every one of them is legal.
+fn replay(start: &Board, moves: &[Move]) -> Option<Board> {
+    let mut board = *start;
+    board.apply_forced();
+    for mv in moves {
+        if !board.legal_moves().contains(mv) { return None }
+        board.apply_move(*mv);
+    }
+    Some(board)
+}
+
+fn wins(start: &Board, moves: &[Move]) -> bool {
+    replay(start, moves).is_some_and(|board| board.is_won())
+}
+
+/// Where `from`'s columns went on `to`, which has the same state: slot `i` of `to` is slot `self.0[i]` of `from`.
+fn matching_columns(from: &BoardInfo, to: &BoardInfo) -> Columns {
+    let mut used = [false; 11];
+    Columns(to.tableau.map(|top| {
+        let i = (0..11).find(|&i| !used[i] && from.tableau[i] == top).expect("states differ");
+        used[i] = true;
+        i as u8
+    }))
+}
+
+/// Whether `mv` is one of `board`'s legal moves, without listing them all.
+fn is_legal(board: &Board, mv: Move) -> bool {
+    mv.src != mv.dst && board.top(mv.src).info().is_card() && board.accepts(mv.dst, board.top(mv.src))
+}
+
+/// Make `moves` in turn from `after`, reached by `used`, offering each position on the way to `consider`, until one of
+/// them isn't legal.
+fn follow(mut after: Board, used: &[Move], moves: impl Iterator<Item = Move>, consider: &mut impl FnMut(&[Move], &Board)) {
+    let mut used = used.to_vec();
+    for mv in moves {
+        if !is_legal(&after, mv) { break }
+        after.apply_move(mv);
+        used.push(mv);
+        consider(&used, &after);
+    }
+}
+
+/// Every position a solution passes through, and the last step at which it's in each state, by Zobrist hash.
+struct Path {
+    moves: Vec<Move>,
+    boards: Vec<Board>,
+    last_seen: HashMap<u64, usize>,
+}
+
+impl Path {
+    fn new(start: &Board, moves: Vec<Move>) -> Self {
+        let mut board = *start;
+        board.apply_forced();
+        let mut boards = vec![board];
+        for mv in &moves {
+            board.apply_move(*mv);
+            boards.push(board);
+        }
+        let last_seen = boards.iter().enumerate().map(|(j, board)| (board.zobrist(), j)).collect();
+        Path { moves, boards, last_seen }
+    }
+
+    /// The quickest way found from `here`, which is step `i` with its columns reordered by `columns`, to a later
+    /// step than the path itself would reach in as many moves: that step, the moves taking it, and where they end up.
+    fn shortcut(&self, i: usize, here: &Board, columns: Columns) -> Option<(usize, Vec<Move>, Board)> {
+        let mut best: Option<(usize, Vec<Move>, Board)> = None;
+        let mut consider = |used: &[Move], after: &Board| {
+            let Some(&j) = self.last_seen.get(&after.zobrist()) else { return };
+            if j <= i + used.len() || self.boards[j].state != after.state { return }
+            if best.as_ref().is_none_or(|(k, moves, _)| j - used.len() > k - moves.len()) {
+                best = Some((j, used.to_vec(), *after));
+            }
+        };
+
+        consider(&[], here);
+        for mv in &here.legal_moves() {
+            let mut after = *here;
+            after.apply_move(*mv);
+            consider(&[*mv], &after);
+        }
+
+        let window: Vec<Move> = self.moves[i..self.moves.len().min(i + WINDOW)].iter().map(|mv| columns.map_move(*mv)).collect();
+        for first in 0..window.len() {
+            for second in first..window.len() {
+                let kept = window.iter().enumerate().filter(|&(k, _)| k != first && k != second).map(|(_, mv)| *mv);
+                follow(*here, &[], kept, &mut consider);
+            }
+        }
+
+        // two neighbouring moves the other way round, as when two cards would be better stacked the other way up,
+        // with perhaps one of the moves after them left out
+        let mut before = *here;
+        for k in 1..window.len() {
+            let swapped = [window[k], window[k - 1]];
+            for skip in k + 1..=window.len() {
+                let kept = (k + 1..window.len()).filter(|&r| r != skip).map(|r| window[r]);
+                follow(before, &window[..k - 1], swapped.into_iter().chain(kept), &mut consider);
+            }
+            before.apply_move(window[k - 1]);
+        }
+        best
+    }
+
+    /// Follow the path from the start, taking every shortcut on the way.
+    fn cut(&self) -> Vec<Move> {
+        let mut board = self.boards[0];
+        let mut columns = Columns::IDENTITY;
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < self.moves.len() {
+            if let Some((j, used, after)) = self.shortcut(i, &board, columns) {
+                out.extend(used);
+                board = after;
+                columns = matching_columns(&board.info, &self.boards[j].info);
+                i = j;
+            } else {
+                let mv = columns.map_move(self.moves[i]);
+                board.apply_move(mv);
+                out.push(mv);
+                i += 1;
+            }
+        }
+        out
+    }
+}
+
+/// A solution no longer than `moves` that still wins from `start`, with detours cut out.
+///
+/// `moves` are player moves on `start`'s own columns, made after its forced moves, as the solvers return them. If
+/// they don't win from `start`, or what's left after cutting somehow doesn't, they're returned unchanged.
+pub fn shorten(start: &Board, moves: &[Move]) -> Vec<Move> {
+    if !wins(start, moves) { return moves.to_vec() }
+    let mut best = moves.to_vec();
+    // a shortcut passes through positions the solution didn't, which may open up more shortcuts behind it
+    loop {
+        let shorter = Path::new(start, best.clone()).cut();
+        if shorter.len() == best.len() { break }
+        best = shorter;
+    }
+    if !wins(start, &best) { return moves.to_vec() }
+    best
+}
+
+
+#[cfg(test)]
+mod shorten_tests {
+    use super::*;
+    use crate::{deal::deal, solve::{solve_best_first, SolveResult}, text::fixtures::*};
+
+    fn solved(seed: u64) -> (Board, Vec<Move>) {
+        let board: Board = deal(seed).into();
+        match solve_best_first(board) {
+            SolveResult::Solved(moves) => (board, moves),
+            other => panic!("seed {} not solved: {:?}", seed, other),
+        }
+    }
+
+    #[test]
+    fn removes_detour() {
+        let (start, moves) = solved(9);
+        let mut board = start;
+        board.apply_forced();
+        // somewhere along the way, a card that can be moved and put straight back where it was, columns and all
+        let mut detour = None;
+        for (k, mv) in moves.iter().enumerate() {
+            detour = board.legal_moves().iter().find_map(|&there| {
+                let mut after = board;
+                after.apply_move(there);
+                after.legal_moves().iter().copied()
+                    .find(|&back| { let mut b = after; b.apply_move(back); b.state == board.state && b.info == board.info })
+                    .map(|back| (k, [there, back]))
+            });
+            if detour.is_some() { break }
+            board.apply_move(*mv);
+        }
+        let (k, detour) = detour.expect("no reversible move");
+
+        let padded: Vec<Move> = moves[..k].iter().chain(&detour).chain(&moves[k..]).copied().collect();
+        assert!(wins(&start, &padded));
+        let shorter = shorten(&start, &padded);
+        assert!(wins(&start, &shorter));
+        assert!(shorter.len() <= moves.len(), "{} > {}", shorter.len(), moves.len());
+    }
+
+    #[test]
+    fn solver_output_stays_a_win() {
+        for seed in [9, 10, 11] {
+            let (start, moves) = solved(seed);
+            let shorter = shorten(&start, &moves);
+            assert!(wins(&start, &shorter), "seed {}", seed);
+            assert!(shorter.len() <= moves.len(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn swaps_moves() {
+        // |9 waits in the freecell, holding up the minor foundations, while |8 is under |T
+        let layout = "|8 |T\n|K |J |Q\n\n$9 $J\n$T\n$Q\n$K\n*9 *J\n*T\n*Q\n*K";
+        let start = board(layout);
+        use MoveLoc::*;
+        let mv = |src, dst| Move { src, dst };
+        // putting |9 down first and |T on it buries |9, so |T has to move again
+        let moves = [mv(Freecell, Tableau(2)), mv(Tableau(0), Tableau(2)), mv(Tableau(2), Tableau(0)),
+            mv(Tableau(1), Tableau(0)), mv(Tableau(3), Tableau(0)), mv(Tableau(7), Tableau(0))];
+        assert!(wins(&start, &moves));
+        // the other way round, with |T down first, scores both straight away
+        assert_eq!(shorten(&start, &moves), [mv(Tableau(0), Tableau(2)), mv(Freecell, Tableau(2)),
+            mv(Tableau(1), Tableau(0)), mv(Tableau(3), Tableau(0)), mv(Tableau(7), Tableau(0))]);
+    }
+
+    #[test]
+    fn leaves_non_solutions_alone() {
+        let (start, moves) = solved(9);
+        let partial = &moves[..moves.len() / 2];
+        assert_eq!(shorten(&start, partial), partial);
+    }
+}
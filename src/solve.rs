@@ -0,0 +1,789 @@
+//! Searches for a sequence of player moves that wins a board.
+//! 
+//! 
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::mem::size_of;
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+use std::time::{Duration, Instant};
+use crate::certificate::Certificate;
+use crate::heuristic::{foundation, min_moves, score};
+use crate::prune::{useful_moves, LastMove, Pruned};
+use crate::state::*;
+
+/// Outcome of a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveResult {
+    /// Player moves that win the game, starting from the given board and numbering its columns as given,
+    /// even though the searches work on canonicalized boards.
+    Solved(Vec<Move>),
+    /// Every reachable position was explored without finding a win. `states_explored` is the search's
+    /// [`SearchStats::nodes_expanded`], which for IDA* counts every iteration.
+    Unsolvable { states_explored: usize },
+    /// The search stopped at a limit from its [`SolveConfig`] before reaching either answer.
+    LimitReached {
+        /// Player moves from the given board, numbered as its columns, to the position with the most cards scored
+        /// that the search reached.
+        best_partial: Vec<Move>,
+        stats: SearchStats,
+    },
+}
+
+/// Limits on how much work a search may do before giving up, and what to tell about its progress meanwhile.
+#[derive(Clone, Default)]
+pub struct SolveConfig {
+    /// Most positions to expand.
+    pub max_nodes: Option<usize>,
+    /// Most memory for the search's own data structures, in bytes.
+    pub max_memory_bytes: Option<usize>,
+    /// Longest to run for.
+    pub timeout: Option<Duration>,
+    /// Set from another thread to stop the search early.
+    pub cancel: Arc<AtomicBool>,
+    /// Told how the search is going about every [`PROGRESS_INTERVAL`].
+    pub progress: Option<Arc<dyn Progress>>,
+    /// Try every legal move, not just the ones the dominance pruning keeps, to measure what the pruning saves.
+    pub unpruned: bool,
+}
+
+impl fmt::Debug for SolveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolveConfig")
+            .field("max_nodes", &self.max_nodes)
+            .field("max_memory_bytes", &self.max_memory_bytes)
+            .field("timeout", &self.timeout)
+            .field("cancel", &self.cancel)
+            .field("progress", &self.progress.is_some())
+            .field("unpruned", &self.unpruned)
+            .finish()
+    }
+}
+
+/// Measurements of the work a search did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Positions whose moves were generated.
+    pub nodes_expanded: usize,
+    /// Positions reached by those moves, whether new or not.
+    pub nodes_generated: usize,
+    /// Positions reached that the search threw away for having been there before.
+    pub duplicates: usize,
+    /// Most moves deep the search went.
+    pub max_depth: usize,
+    /// Positions waiting to be expanded: the open set, or for the depth-first searches the stack.
+    pub open: usize,
+    /// Highest [`score`] of any position expanded.
+    pub best_score: i32,
+    /// Largest size reached by the search's own data structures, in bytes.
+    pub peak_bytes: usize,
+    /// Moves skipped by each of the dominance pruning rules.
+    pub pruned: Pruned,
+    /// Time spent searching.
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    /// Positions expanded per second spent searching.
+    pub fn nodes_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.nodes_expanded as f64 / secs } else { 0.0 }
+    }
+}
+
+/// Something to tell how a search is going while it runs, such as a status line.
+pub trait Progress: Send + Sync {
+    /// Called with the search's figures so far, on the thread running the search.
+    fn report(&self, stats: &SearchStats);
+}
+
+impl<F: Fn(&SearchStats) + Send + Sync> Progress for F {
+    fn report(&self, stats: &SearchStats) {
+        self(stats)
+    }
+}
+
+/// How often a search calls its [`Progress`].
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Tracks a search's progress against its [`SolveConfig`].
+struct Limiter<'a> {
+    config: &'a SolveConfig,
+    started: Instant,
+    deadline: Option<Instant>,
+    next_report: Instant,
+}
+
+impl<'a> Limiter<'a> {
+    fn new(config: &'a SolveConfig) -> Self {
+        let started = Instant::now();
+        Self { config, started, deadline: config.timeout.map(|t| started + t), next_report: started + PROGRESS_INTERVAL }
+    }
+
+    /// Whether the search has to stop, given its figures so far and using `bytes` of memory. Also reports the
+    /// figures to the config's [`Progress`] when it's due.
+    fn exceeded(&mut self, stats: &mut SearchStats, bytes: impl FnOnce() -> usize) -> bool {
+        if self.config.max_nodes.is_some_and(|max| stats.nodes_expanded >= max) { return true }
+        // reading the clock and sizing up memory are the slow parts, so only do them every so often
+        if !stats.nodes_expanded.is_multiple_of(256) { return false }
+
+        let now = Instant::now();
+        let report = self.config.progress.as_ref().filter(|_| now >= self.next_report);
+        if report.is_some() || self.config.max_memory_bytes.is_some() {
+            stats.peak_bytes = stats.peak_bytes.max(bytes());
+        }
+        if let Some(progress) = report {
+            stats.elapsed = now - self.started;
+            progress.report(stats);
+            self.next_report = now + PROGRESS_INTERVAL;
+        }
+        self.config.cancel.load(Ordering::Relaxed)
+            || self.deadline.is_some_and(|d| now >= d)
+            || self.config.max_memory_bytes.is_some_and(|max| stats.peak_bytes >= max)
+    }
+
+    /// Fill in the last of a finished search's figures, it having used `bytes` of memory at the end.
+    fn finish(&self, stats: &mut SearchStats, bytes: usize) {
+        stats.peak_bytes = stats.peak_bytes.max(bytes);
+        stats.elapsed = self.started.elapsed();
+    }
+}
+
+/// The position with the most cards scored that a search has reached so far, and the moves that reached it.
+struct Deepest {
+    scored: u32,
+    moves: Vec<Move>,
+}
+
+impl Deepest {
+    fn new(board: &Board) -> Self {
+        Self { scored: foundation(&board.info), moves: Vec::new() }
+    }
+
+    /// Note reaching `board`, working out the moves that reached it only if it's the new deepest.
+    fn offer(&mut self, board: &Board, moves: impl FnOnce() -> Vec<Move>) {
+        let scored = foundation(&board.info);
+        if scored > self.scored {
+            self.scored = scored;
+            self.moves = moves();
+        }
+    }
+}
+
+/// The moves a search tries from `board`, reached by `last`: the [`useful_moves`], unless `config` says to try them all.
+fn moves_from(board: &Board, last: Option<LastMove>, config: &SolveConfig, stats: &mut SearchStats) -> MoveList {
+    if config.unpruned { board.legal_moves() } else { useful_moves(board, last, &mut stats.pruned) }
+}
+
+/// One level of the depth-first search: the moves available there and how far through them we are.
+struct Frame {
+    moves: MoveList,
+    next: usize,
+}
+
+/// Exhaustive depth-first search, skipping any position that has been seen before.
+pub fn solve(board: Board) -> SolveResult {
+    solve_with(board, &Default::default()).0
+}
+
+/// [`solve`], stopping at the limits in `config` and reporting what the search took.
+pub fn solve_with(board: Board, config: &SolveConfig) -> (SolveResult, SearchStats) {
+    let (result, stats, _) = dfs(board, config);
+    (result, stats)
+}
+
+/// [`solve`], also producing a [`Certificate`] of every reachable position when the board turns out unsolvable.
+pub fn solve_certified(board: Board) -> (SolveResult, Option<Certificate>) {
+    solve_certified_with(board, &Default::default())
+}
+
+/// [`solve_certified`], stopping at the limits in `config`, in which case there's no certificate.
+pub fn solve_certified_with(board: Board, config: &SolveConfig) -> (SolveResult, Option<Certificate>) {
+    let (result, _, visited) = dfs(board, config);
+    let cert = match result {
+        SolveResult::Unsolvable { .. } => Some(visited.into_iter().map(|b| b.state).collect()),
+        _ => None,
+    };
+    (result, cert)
+}
+
+/// The depth-first search behind [`solve`], handing back its visited set as well.
+fn dfs(start: Board, config: &SolveConfig) -> (SolveResult, SearchStats, HashSet<Board>) {
+    let mut board = start;
+    board.apply_forced();
+    board.canonicalize();
+    let mut limiter = Limiter::new(config);
+    let mut stats = SearchStats::default();
+    let mut deepest = Deepest::new(&board);
+
+    let mut visited: HashSet<Board> = Default::default();
+    visited.insert(board);
+    if board.is_won() { return (SolveResult::Solved(Vec::new()), stats, visited) }
+
+    let mut stack = vec![Frame { moves: moves_from(&board, None, config, &mut stats), next: 0 }];
+    let mut path: Vec<(Move, Undo)> = Vec::new();
+    stats.nodes_expanded = 1;
+    stats.best_score = score(&board);
+
+    let bytes = |visited: &HashSet<Board>, stack: &Vec<Frame>, path: &Vec<(Move, Undo)>| {
+        visited.capacity() * (size_of::<Board>() + 1)
+            + stack.capacity() * size_of::<Frame>()
+            + path.capacity() * size_of::<(Move, Undo)>()
+    };
+
+    while let Some(frame) = stack.last_mut() {
+        if frame.next == frame.moves.len() {
+            stack.pop();
+            if let Some((_, undo)) = path.pop() { board.undo_move(undo) }
+            continue;
+        }
+
+        let mv = frame.moves[frame.next];
+        frame.next += 1;
+
+        let undo = board.apply_move(mv);
+        board.canonicalize();
+        stats.nodes_generated += 1;
+        if !visited.insert(board) {
+            stats.duplicates += 1;
+            board.undo_move(undo);
+            continue;
+        }
+
+        let last = LastMove::after(&undo);
+        path.push((mv, undo));
+        if board.is_won() {
+            limiter.finish(&mut stats, bytes(&visited, &stack, &path));
+            let moves: Vec<Move> = path.into_iter().map(|(mv, _)| mv).collect();
+            return (SolveResult::Solved(physical_moves(start, &moves)), stats, visited);
+        }
+        deepest.offer(&board, || path.iter().map(|(mv, _)| *mv).collect());
+        stats.open = stack.len();
+        if limiter.exceeded(&mut stats, || bytes(&visited, &stack, &path)) {
+            limiter.finish(&mut stats, bytes(&visited, &stack, &path));
+            let best_partial = physical_moves(start, &deepest.moves);
+            return (SolveResult::LimitReached { best_partial, stats }, stats, visited);
+        }
+        stats.nodes_expanded += 1;
+        stats.max_depth = stats.max_depth.max(path.len());
+        stats.best_score = stats.best_score.max(score(&board));
+        stack.push(Frame { moves: moves_from(&board, last, config, &mut stats), next: 0 });
+    }
+
+    limiter.finish(&mut stats, bytes(&visited, &stack, &path));
+    (SolveResult::Unsolvable { states_explored: stats.nodes_expanded }, stats, visited)
+}
+
+/// A position reached by one of the best-first searches, and how it was reached.
+struct Node {
+    board: Board,
+    parent: usize,
+    mv: Move,
+    last: Option<LastMove>,
+    depth: u32,
+}
+
+/// Walk parent links back from `idx` to recover the moves that reached it.
+fn path_to(nodes: &[Node], mut idx: usize) -> Vec<Move> {
+    let mut moves = Vec::new();
+    while idx != 0 {
+        moves.push(nodes[idx].mv);
+        idx = nodes[idx].parent;
+    }
+    moves.reverse();
+    moves
+}
+
+/// How many points of [`score`] one extra move must earn to be worth making.
+pub const DEPTH_COST: i32 = 2;
+
+/// Best-first search, always expanding the open position with the best [`score`] for its depth, newest first on ties.
+pub fn solve_best_first(board: Board) -> SolveResult {
+    solve_best_first_with(board, &Default::default()).0
+}
+
+/// [`solve_best_first`], stopping at the limits in `config` and reporting what the search took.
+pub fn solve_best_first_with(start: Board, config: &SolveConfig) -> (SolveResult, SearchStats) {
+    let mut board = start;
+    board.apply_forced();
+    board.canonicalize();
+    let mut limiter = Limiter::new(config);
+    let mut stats = SearchStats::default();
+    let mut deepest = Deepest::new(&board);
+
+    let mut visited: HashSet<Board> = Default::default();
+    visited.insert(board);
+
+    let filler = Move { src: MoveLoc::Freecell, dst: MoveLoc::Freecell };
+    let mut nodes = vec![Node { board, parent: 0, mv: filler, last: None, depth: 0 }];
+    let mut open = BinaryHeap::new();
+    open.push((0, 0));
+
+    let bytes = |nodes: &Vec<Node>, visited: &HashSet<Board>, open: &BinaryHeap<(i32, usize)>| {
+        nodes.capacity() * size_of::<Node>()
+            + visited.capacity() * (size_of::<Board>() + 1)
+            + open.capacity() * size_of::<(i32, usize)>()
+    };
+
+    while let Some((_, idx)) = open.pop() {
+        let mut board = nodes[idx].board;
+        if board.is_won() {
+            limiter.finish(&mut stats, bytes(&nodes, &visited, &open));
+            return (SolveResult::Solved(physical_moves(start, &path_to(&nodes, idx))), stats);
+        }
+        stats.open = open.len();
+        if limiter.exceeded(&mut stats, || bytes(&nodes, &visited, &open)) {
+            limiter.finish(&mut stats, bytes(&nodes, &visited, &open));
+            let best_partial = physical_moves(start, &deepest.moves);
+            return (SolveResult::LimitReached { best_partial, stats }, stats);
+        }
+        stats.nodes_expanded += 1;
+        stats.max_depth = stats.max_depth.max(nodes[idx].depth as usize);
+        stats.best_score = stats.best_score.max(score(&board));
+        let depth = nodes[idx].depth + 1;
+
+        for mv in &moves_from(&board, nodes[idx].last, config, &mut stats) {
+            let undo = board.apply_move(*mv);
+            board.canonicalize();
+            stats.nodes_generated += 1;
+            if visited.insert(board) {
+                let priority = score(&board) - DEPTH_COST * depth as i32;
+                open.push((priority, nodes.len()));
+                nodes.push(Node { board, parent: idx, mv: *mv, last: LastMove::after(&undo), depth });
+                deepest.offer(&board, || path_to(&nodes, nodes.len() - 1));
+            } else {
+                stats.duplicates += 1;
+            }
+            board.undo_move(undo);
+        }
+    }
+
+    limiter.finish(&mut stats, bytes(&nodes, &visited, &open));
+    (SolveResult::Unsolvable { states_explored: stats.nodes_expanded }, stats)
+}
+
+/// A* search for a solution with the fewest player moves, with the forced moves that follow each one counted as free.
+///
+/// Guided by the consistent lower bound [`min_moves`], so the first win taken off the open set is optimal and the
+/// length of the returned move list is the proven minimum.
+pub fn solve_optimal(start: Board) -> SolveResult {
+    solve_optimal_with(start, &Default::default()).0
+}
+
+/// [`solve_optimal`], stopping at the limits in `config` and reporting what the search took.
+pub fn solve_optimal_with(start: Board, config: &SolveConfig) -> (SolveResult, SearchStats) {
+    let mut board = start;
+    board.apply_forced();
+    board.canonicalize();
+    let mut limiter = Limiter::new(config);
+    let mut stats = SearchStats::default();
+    let mut deepest = Deepest::new(&board);
+
+    let mut best: HashMap<Board, u32> = Default::default();
+    best.insert(board, 0);
+
+    let filler = Move { src: MoveLoc::Freecell, dst: MoveLoc::Freecell };
+    let mut nodes = vec![Node { board, parent: 0, mv: filler, last: None, depth: 0 }];
+    let mut open = BinaryHeap::new();
+    open.push((Reverse(min_moves(&board)), 0, 0));
+
+    let bytes = |nodes: &Vec<Node>, best: &HashMap<Board, u32>, open: &BinaryHeap<(Reverse<u32>, u32, usize)>| {
+        nodes.capacity() * size_of::<Node>()
+            + best.capacity() * (size_of::<(Board, u32)>() + 1)
+            + open.capacity() * size_of::<(Reverse<u32>, u32, usize)>()
+    };
+
+    while let Some((_, depth, idx)) = open.pop() {
+        let mut board = nodes[idx].board;
+        if best[&board] < depth { continue }
+        if board.is_won() {
+            limiter.finish(&mut stats, bytes(&nodes, &best, &open));
+            return (SolveResult::Solved(physical_moves(start, &path_to(&nodes, idx))), stats);
+        }
+        stats.open = open.len();
+        if limiter.exceeded(&mut stats, || bytes(&nodes, &best, &open)) {
+            limiter.finish(&mut stats, bytes(&nodes, &best, &open));
+            let best_partial = physical_moves(start, &deepest.moves);
+            return (SolveResult::LimitReached { best_partial, stats }, stats);
+        }
+        stats.nodes_expanded += 1;
+        stats.max_depth = stats.max_depth.max(depth as usize);
+        stats.best_score = stats.best_score.max(score(&board));
+        let depth = depth + 1;
+
+        for mv in &moves_from(&board, nodes[idx].last, config, &mut stats) {
+            let undo = board.apply_move(*mv);
+            board.canonicalize();
+            stats.nodes_generated += 1;
+            if best.get(&board).is_none_or(|g| depth < *g) {
+                best.insert(board, depth);
+                open.push((Reverse(depth + min_moves(&board)), depth, nodes.len()));
+                nodes.push(Node { board, parent: idx, mv: *mv, last: LastMove::after(&undo), depth });
+                deepest.offer(&board, || path_to(&nodes, nodes.len() - 1));
+            } else {
+                stats.duplicates += 1;
+            }
+            board.undo_move(undo);
+        }
+    }
+
+    limiter.finish(&mut stats, bytes(&nodes, &best, &open));
+    (SolveResult::Unsolvable { states_explored: stats.nodes_expanded }, stats)
+}
+
+/// Fixed-size transposition table, where each position can only live in one slot and evicts whatever was there.
+struct Table {
+    hasher: RandomState,
+    slots: Vec<(Board, u32)>,
+}
+
+impl Table {
+    const EMPTY: u32 = u32::MAX;
+
+    fn with_bytes(bytes: usize) -> Self {
+        let len = bytes / size_of::<(Board, u32)>();
+        Self { hasher: Default::default(), slots: vec![(Board::default(), Self::EMPTY); len] }
+    }
+
+    fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| slot.1 = Self::EMPTY);
+    }
+
+    /// Record reaching `board` at `depth`, returning false if it was already reached at the same or lesser depth.
+    fn visit(&mut self, board: &Board, depth: u32) -> bool {
+        if self.slots.is_empty() { return true }
+        let i = self.hasher.hash_one(board) as usize % self.slots.len();
+        let slot = &mut self.slots[i];
+        if slot.1 != Self::EMPTY && slot.1 <= depth && slot.0 == *board {
+            false
+        } else {
+            *slot = (*board, depth);
+            true
+        }
+    }
+}
+
+/// Default memory budget for the IDA* transposition table.
+pub const DEFAULT_TABLE_BYTES: usize = 64 << 20;
+
+/// Iterative-deepening A*, walking the tree in place and remembering at most as many positions as fit in `table_bytes`.
+///
+/// Each iteration is a depth-first search that cuts off any position whose cost, counting [`DEPTH_COST`] per move
+/// against its [`score`], exceeds the bound; the next iteration raises the bound to the cheapest position cut off.
+/// The transposition table only prunes positions already reached at the same or lesser depth, and is a fixed size,
+/// so a smaller budget just means revisiting more positions rather than running out of memory.
+///
+/// Only a table that holds every reachable position lets an unsolvable deal finish, though: once positions are
+/// evicted, each iteration can walk round cycles through them a little further than the last, cutting off forever.
+/// So unless the deal is known to be small, give [`solve_ida_with`] a node limit or timeout.
+pub fn solve_ida(start: Board, table_bytes: usize) -> SolveResult {
+    solve_ida_with(start, table_bytes, &Default::default()).0
+}
+
+/// [`solve_ida`], stopping at the limits in `config` and reporting what the search took.
+///
+/// A memory limit also caps the table, at half the limit so the search's stack has room too.
+pub fn solve_ida_with(start: Board, table_bytes: usize, config: &SolveConfig) -> (SolveResult, SearchStats) {
+    let mut board = start;
+    board.apply_forced();
+    board.canonicalize();
+    let mut limiter = Limiter::new(config);
+    let mut stats = SearchStats::default();
+    let mut deepest = Deepest::new(&board);
+    if board.is_won() { return (SolveResult::Solved(Vec::new()), stats) }
+
+    let cost = |board: &Board, depth: usize| DEPTH_COST * depth as i32 - score(board);
+    let table_bytes = config.max_memory_bytes.map_or(table_bytes, |max| table_bytes.min(max / 2));
+    let mut table = Table::with_bytes(table_bytes);
+    let mut bound = cost(&board, 0);
+
+    let bytes = |table: &Table, stack: &Vec<Frame>, path: &Vec<(Move, Undo)>| {
+        table.slots.capacity() * size_of::<(Board, u32)>()
+            + stack.capacity() * size_of::<Frame>()
+            + path.capacity() * size_of::<(Move, Undo)>()
+    };
+
+    loop {
+        table.clear();
+        table.visit(&board, 0);
+        let mut next_bound = None;
+
+        let mut stack = vec![Frame { moves: moves_from(&board, None, config, &mut stats), next: 0 }];
+        let mut path: Vec<(Move, Undo)> = Vec::new();
+        stats.nodes_expanded += 1;
+        stats.best_score = stats.best_score.max(score(&board));
+
+        while let Some(frame) = stack.last_mut() {
+            if frame.next == frame.moves.len() {
+                stack.pop();
+                if let Some((_, undo)) = path.pop() { board.undo_move(undo) }
+                continue;
+            }
+
+            let mv = frame.moves[frame.next];
+            frame.next += 1;
+
+            let undo = board.apply_move(mv);
+            board.canonicalize();
+            stats.nodes_generated += 1;
+            let depth = path.len() as u32 + 1;
+
+            let f = cost(&board, depth as usize);
+            if f > bound {
+                next_bound = Some(next_bound.map_or(f, |b: i32| b.min(f)));
+                board.undo_move(undo);
+                continue;
+            }
+
+            if !table.visit(&board, depth) {
+                stats.duplicates += 1;
+                board.undo_move(undo);
+                continue;
+            }
+
+            let last = LastMove::after(&undo);
+            path.push((mv, undo));
+            if board.is_won() {
+                limiter.finish(&mut stats, bytes(&table, &stack, &path));
+                let moves: Vec<Move> = path.into_iter().map(|(mv, _)| mv).collect();
+                return (SolveResult::Solved(physical_moves(start, &moves)), stats);
+            }
+            deepest.offer(&board, || path.iter().map(|(mv, _)| *mv).collect());
+            stats.open = stack.len();
+            if limiter.exceeded(&mut stats, || bytes(&table, &stack, &path)) {
+                limiter.finish(&mut stats, bytes(&table, &stack, &path));
+                let best_partial = physical_moves(start, &deepest.moves);
+                return (SolveResult::LimitReached { best_partial, stats }, stats);
+            }
+            stats.nodes_expanded += 1;
+            stats.max_depth = stats.max_depth.max(path.len());
+            stats.best_score = stats.best_score.max(score(&board));
+            stack.push(Frame { moves: moves_from(&board, last, config, &mut stats), next: 0 });
+        }
+
+        limiter.finish(&mut stats, bytes(&table, &stack, &path));
+        match next_bound {
+            Some(b) => bound = b,
+            None => return (SolveResult::Unsolvable { states_explored: stats.nodes_expanded }, stats),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod solve_tests {
+    use super::*;
+    use crate::text::fixtures::*;
+
+    fn replay(mut board: Board, moves: &[Move]) -> Board {
+        board.apply_forced();
+        for mv in moves {
+            assert!(board.legal_moves().contains(mv), "illegal move {:?}", mv);
+            board.apply_move(*mv);
+        }
+        board
+    }
+
+    #[test]
+    fn solve_won() {
+        assert_eq!(solve(Board::default()), SolveResult::Solved(vec![]));
+    }
+
+    #[test]
+    fn solve_nearly_won() {
+        let board = board(NEARLY_WON);
+        match solve(board) {
+            SolveResult::Solved(moves) => assert!(replay(board, &moves).is_won()),
+            other => panic!("not solved: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn best_first_nearly_won() {
+        let board = board(NEARLY_WON);
+        match solve_best_first(board) {
+            SolveResult::Solved(moves) => assert!(replay(board, &moves).is_won()),
+            other => panic!("not solved: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn best_first_stuck() {
+        let board = board(STUCK);
+        assert_eq!(solve_best_first(board), SolveResult::Unsolvable { states_explored: 1 });
+    }
+
+    #[test]
+    fn ida_nearly_won() {
+        let board = board(NEARLY_WON);
+        match solve_ida(board, DEFAULT_TABLE_BYTES) {
+            SolveResult::Solved(moves) => assert!(replay(board, &moves).is_won()),
+            other => panic!("not solved: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ida_without_table() {
+        let board = board(NEARLY_WON);
+        match solve_ida(board, 0) {
+            SolveResult::Solved(moves) => assert!(replay(board, &moves).is_won()),
+            other => panic!("not solved: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ida_expands_less_pruned() {
+        // without a table only the pruning keeps the search from trying each of the eight empty columns in turn
+        let board = board(OPEN_COLUMNS);
+        let (result, stats) = solve_ida_with(board, 0, &Default::default());
+        assert!(matches!(result, SolveResult::Solved(_)), "{:?}", result);
+        assert!(stats.pruned.total() > 0);
+
+        let config = SolveConfig { unpruned: true, ..Default::default() };
+        let (result, unpruned) = solve_ida_with(board, 0, &config);
+        assert!(matches!(result, SolveResult::Solved(_)), "{:?}", result);
+        assert_eq!(unpruned.pruned, Pruned::default());
+        assert!(stats.nodes_expanded * 10 < unpruned.nodes_expanded, "{} vs {}", stats.nodes_expanded, unpruned.nodes_expanded);
+    }
+
+    #[test]
+    fn ida_stuck() {
+        let board = board(STUCK);
+        assert_eq!(solve_ida(board, DEFAULT_TABLE_BYTES), SolveResult::Unsolvable { states_explored: 1 });
+    }
+
+    #[test]
+    fn optimal_nearly_won() {
+        let board = board(NEARLY_WON);
+        match solve_optimal(board) {
+            SolveResult::Solved(moves) => {
+                assert!(replay(board, &moves).is_won());
+                // K* off Q| unlocks both wands and stars, then two each to dig out J! and J$
+                assert_eq!(moves.len(), 5);
+            },
+            other => panic!("not solved: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn optimal_no_longer_than_others() {
+        let board = board(NEARLY_WON);
+        let SolveResult::Solved(optimal) = solve_optimal(board) else { panic!() };
+        for result in [solve(board), solve_best_first(board), solve_ida(board, DEFAULT_TABLE_BYTES)] {
+            let SolveResult::Solved(moves) = result else { panic!() };
+            assert!(optimal.len() <= moves.len());
+        }
+    }
+
+    #[test]
+    fn optimal_stuck() {
+        let board = board(STUCK);
+        assert_eq!(solve_optimal(board), SolveResult::Unsolvable { states_explored: 1 });
+    }
+
+    #[test]
+    fn best_first_limits() {
+        let board = board(NEARLY_WON);
+        let config = SolveConfig { max_nodes: Some(1), ..Default::default() };
+        let (result, stats) = solve_best_first_with(board, &config);
+        assert!(matches!(result, SolveResult::LimitReached { stats: s, .. } if s == stats));
+        assert_eq!(stats.nodes_expanded, 1);
+
+        let (result, stats) = solve_best_first_with(board, &Default::default());
+        assert!(matches!(result, SolveResult::Solved(_)));
+        assert!(stats.nodes_expanded > 1);
+        assert!(stats.peak_bytes > 0);
+    }
+
+    type Solver = fn(Board, &SolveConfig) -> (SolveResult, SearchStats);
+    const SOLVERS: [Solver; 4] = [solve_with, solve_best_first_with, solve_optimal_with,
+        |board, config| solve_ida_with(board, DEFAULT_TABLE_BYTES, config)];
+
+    #[test]
+    fn every_mode_counts_states_alike() {
+        // a deal none of them can win, so each explores everything it will
+        let board: Board = crate::deal::deal(2).into();
+        for solver in SOLVERS {
+            let (result, stats) = solver(board, &Default::default());
+            assert_eq!(result, SolveResult::Unsolvable { states_explored: stats.nodes_expanded });
+        }
+    }
+
+    #[test]
+    fn every_mode_stops_at_node_limit() {
+        let board: Board = crate::deal::deal(9).into();
+        let config = SolveConfig { max_nodes: Some(300), ..Default::default() };
+        for solver in SOLVERS {
+            let (result, stats) = solver(board, &config);
+            let SolveResult::LimitReached { best_partial, .. } = result else { panic!("{:?}", result) };
+            assert!(stats.nodes_expanded <= 300);
+            assert!(stats.nodes_generated >= stats.nodes_expanded && stats.duplicates < stats.nodes_generated);
+            assert!(stats.max_depth > 0 && stats.open > 0 && stats.best_score > 0);
+            assert!(stats.elapsed > Duration::ZERO);
+            // the partial line is playable, and gets further than where the search started
+            let reached = replay(board, &best_partial);
+            let mut start = board;
+            start.apply_forced();
+            assert!(foundation(&reached.info) > foundation(&start.info));
+        }
+    }
+
+    #[test]
+    fn every_mode_stops_when_cancelled() {
+        let board: Board = crate::deal::deal(9).into();
+        let config = SolveConfig::default();
+        config.cancel.store(true, Ordering::Relaxed);
+        for solver in SOLVERS {
+            let (result, stats) = solver(board, &config);
+            assert!(matches!(result, SolveResult::LimitReached { .. }), "{:?}", result);
+            assert!(stats.nodes_expanded <= 256);
+        }
+    }
+
+    #[test]
+    fn every_mode_stops_at_memory_limit() {
+        let board: Board = crate::deal::deal(9).into();
+        let config = SolveConfig { max_memory_bytes: Some(1 << 20), ..Default::default() };
+        for solver in &SOLVERS[..3] {
+            let (result, stats) = solver(board, &config);
+            assert!(matches!(result, SolveResult::LimitReached { .. }), "{:?}", result);
+            assert!(stats.peak_bytes >= 1 << 20);
+        }
+        // IDA* shrinks its table to fit instead, and this deal doesn't need a deep stack
+        let (result, stats) = SOLVERS[3](board, &config);
+        assert!(matches!(result, SolveResult::Solved(_)), "{:?}", result);
+        assert!(stats.peak_bytes < 1 << 20);
+    }
+
+    #[test]
+    fn progress_reports_while_searching() {
+        let board: Board = crate::deal::deal(9).into();
+        let mut config = SolveConfig::default();
+        let cancel = config.cancel.clone();
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = reports.clone();
+        // stop the search at the first report, which can't come before the search has run a while
+        config.progress = Some(Arc::new(move |stats: &SearchStats| {
+            seen.lock().unwrap().push(*stats);
+            cancel.store(true, Ordering::Relaxed);
+        }));
+
+        let (result, stats) = solve_optimal_with(board, &config);
+        assert!(matches!(result, SolveResult::LimitReached { .. }), "{:?}", result);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        let report = reports[0];
+        assert!(report.elapsed >= PROGRESS_INTERVAL);
+        assert_eq!(report.nodes_expanded, stats.nodes_expanded);
+        assert!(report.nodes_generated > report.duplicates && report.open > 0);
+        assert!(report.nodes_per_sec() > 0.0);
+    }
+
+    #[test]
+    fn solve_stuck() {
+        let board = board(STUCK);
+        assert_eq!(board.info.freecell, "18".parse().unwrap());
+        assert_eq!(solve(board), SolveResult::Unsolvable { states_explored: 1 });
+    }
+}
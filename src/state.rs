@@ -1,5 +1,5 @@
 use core::panic;
-use std::{cmp::{max, min}, hash::Hash, iter::once};
+use std::{cmp::{max, min}, hash::Hash, iter::once, ops::Deref};
 
 /// Packed single-byte representation of a FF card, or of a few other states needed for the algorithm.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -95,10 +95,7 @@ pub enum CardInfo {
 use CardInfo::*;
 impl CardInfo {
     pub fn is_card(&self) -> bool {
-        match self {
-            Card(_, _) => true,
-            _ => false,
-        }
+        matches!(self, Card(_, _))
     }
     pub fn card_suit(&self) -> Suit {
         match self {
@@ -114,6 +111,19 @@ impl CardInfo {
     }
 }
 
+impl C {
+    /// Whether this card may be placed atop `other`: same suit and adjacent rank, or onto an empty tableau.
+    pub fn stacks_on(self, other: C) -> bool {
+        let si = self.info();
+        let oi = other.info();
+        match oi {
+            Tableau => si.is_card(),
+            Card(suit, rank) => si.is_card() && si.card_suit() == suit && si.card_rank().abs_diff(rank) == 1,
+            _ => false,
+        }
+    }
+}
+
 impl CardInfo {
     pub fn pack(self) -> C {
         C(match self {
@@ -167,7 +177,7 @@ mod card_tests {
 
     #[test]
     fn all_c_have_cardinfo() {
-        let all_c: Vec<C> = (EVERY_BASE..=EVERY_HIGH).map(|i| C(i)).collect();
+        let all_c: Vec<C> = (EVERY_BASE..=EVERY_HIGH).map(C).collect();
         assert_eq!(all_c.len(), EVERY_COUNT.into());
 
         let info_conv: Vec<CardInfo> = all_c.clone().into_iter().map(|x| x.into()).collect();
@@ -202,10 +212,10 @@ mod card_tests {
 
     #[test]
     fn card_properties() {
-        assert_eq!(Magic.is_minor(), false);
-        assert_eq!(Stars.is_minor(), true);
-        assert_eq!(NoCard.is_card(), false);
-        assert_eq!(Card(Wands, 10).is_card(), true);
+        assert!(!Magic.is_minor());
+        assert!(Stars.is_minor());
+        assert!(!NoCard.is_card());
+        assert!(Card(Wands, 10).is_card());
         assert_eq!(Card(Cuups, 8).card_rank(), 8);
         assert_eq!(Card(Swrds, 4).card_suit(), Swrds);
     }
@@ -226,6 +236,8 @@ mod card_tests {
 pub struct Board {
     pub state: BoardState,
     pub info: BoardInfo,
+    /// Zobrist hash of `state`, kept up to date by each move rather than recomputed.
+    zobrist: u64,
 }
 
 /// Core state representing the game's underlying symmetry and equivalence classes.
@@ -251,9 +263,65 @@ pub struct BoardInfo {
 }
 
 
+/// Random key for each card resting on each thing it can rest on, from a fixed SplitMix64 stream.
+static ZOBRIST: [[u64; EVERY_COUNT as usize]; CARDS_COUNT as usize] = {
+    let mut keys = [[0; EVERY_COUNT as usize]; CARDS_COUNT as usize];
+    let mut seed: u64 = 0x5A0B_7157_F0A7_0000;
+    let mut i = 0;
+    while i < keys.len() {
+        let mut j = 0;
+        while j < keys[i].len() {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            keys[i][j] = z ^ (z >> 31);
+            j += 1;
+        }
+        i += 1;
+    }
+    keys
+};
+
+/// The Zobrist key contribution of `card` resting on `at`.
+fn zobrist_key(card: C, at: C) -> u64 {
+    ZOBRIST[card.0 as usize][at.0 as usize]
+}
+
+impl BoardState {
+    /// Zobrist hash computed from scratch; [`Board::zobrist`] gives the same value without the work.
+    pub fn zobrist(&self) -> u64 {
+        self.cards.iter().enumerate().fold(0, |h, (i, at)| h ^ zobrist_key(C(i as u8), *at))
+    }
+}
+
+impl Board {
+    pub fn new(state: BoardState, info: BoardInfo) -> Self {
+        Board { state, info, zobrist: state.zobrist() }
+    }
+
+    /// 64-bit Zobrist hash of the state, updated incrementally as moves are made and undone.
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Recompute the cached hash, for after editing `state` directly.
+    pub fn rehash(&mut self) {
+        self.zobrist = self.state.zobrist();
+    }
+
+    /// Put `card` on `at`, keeping the hash in step.
+    fn place(&mut self, card: C, at: C) {
+        let slot = &mut self.state.cards[card.0 as usize];
+        self.zobrist ^= zobrist_key(card, *slot) ^ zobrist_key(card, at);
+        *slot = at;
+    }
+}
+
 impl Hash for Board {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.state.hash(state);
+        debug_assert_eq!(self.zobrist, self.state.zobrist(), "zobrist hash out of sync with state");
+        state.write_u64(self.zobrist);
     }
 }
 impl PartialEq for Board {
@@ -264,7 +332,7 @@ impl PartialEq for Board {
 impl Eq for Board {}
 impl PartialOrd for Board {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.state.partial_cmp(&other.state)
+        Some(self.cmp(other))
     }
 }
 impl Ord for Board {
@@ -293,15 +361,21 @@ impl Default for BoardInfo {
 impl Default for Board {
     /// Default state is the lexically smallest win.
     fn default() -> Self {
-        Self { state: Default::default(), info: Default::default() }
+        Self::new(Default::default(), Default::default())
     }
 }
 
 impl From<BoardState> for BoardInfo {
     fn from(value: BoardState) -> Self {
-        let mut new = Self::default();
+        let mut new = Self {
+            tableau: [C::TABLEAU; 11],
+            freecell: C::FREECELL,
+            foundation: [C::NO_CARD; 5],
+            down_foundn: C::NO_CARD,
+        };
 
         let mut tab_count = 0;
+        let mut above = [C::NO_CARD; CARDS_COUNT as usize];
 
         // single-pass to find all of the bottom tableau cards and top foundation cards
         for (i, card_state) in value.cards.iter().enumerate() {
@@ -309,37 +383,107 @@ impl From<BoardState> for BoardInfo {
             let cs = cp.info().card_suit();
             
             match card_state.info() {
-                Card(_,_) => (),
+                Card(_,_) => {
+                    let below = &mut above[card_state.0 as usize];
+                    assert_eq!(*below, C::NO_CARD, "bifurcating stack");
+                    *below = cp;
+                },
                 Tableau => {new.tableau[tab_count] = cp; tab_count += 1},
-                Freecell => {assert_eq!(new.freecell.info(), NoCard); new.freecell = cp},
+                Freecell => {assert_eq!(new.freecell, C::FREECELL); new.freecell = cp},
                 DownFoundn => new.down_foundn = min(new.down_foundn, cp),
-                Foundation => new.foundation[cs as usize] = max(new.foundation[cs as usize], cp), 
+                Foundation => {
+                    let f = &mut new.foundation[cs as usize];
+                    *f = if *f == C::NO_CARD { cp } else { max(*f, cp) };
+                },
                 NoCard => panic!("missing card"),
             };
         }
 
         assert!(&new.tableau.windows(2).all(|w| w[0] <= w[1]), "not in sorted order");
-        // would sort them here if necessary, but the cards were already traversed in that order as they were added
-
-        // build each stack up by finding which cards are atop each previous
-        for t in new.tableau.iter_mut() {
-            loop {
-                let mut next_cards = value.cards.iter().filter(|card_state| **card_state == *t);
-                assert!(next_cards.clone().count() <= 1, "bifurcating stack");
-                match next_cards.next() {
-                    Some(cp) => *t = *cp,
-                    None => break,
-                }
+
+        // build each stack up by following which card is atop each previous
+        for t in new.tableau[..tab_count].iter_mut() {
+            while above[t.0 as usize] != C::NO_CARD {
+                *t = above[t.0 as usize];
             }
         }
 
+        // the same column order as Board::canonicalize gives
+        new.tableau.sort();
+
         new
     }
 }
 
+impl Board {
+    /// Check that the auxiliary info agrees with the canonical state, up to the order of the tableau stacks.
+    pub fn validate(&self) {
+        let mut expect = BoardInfo::from(self.state);
+        let mut actual = self.info;
+        expect.tableau.sort();
+        actual.tableau.sort();
+        assert_eq!(expect, actual, "board info out of sync with state");
+        assert_eq!(self.zobrist, self.state.zobrist(), "zobrist hash out of sync with state");
+    }
+}
+
+/// How [`Board::canonicalize`] reordered the tableau: slot `i` now holds the column that was in slot `self.0[i]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Columns(pub [u8; 11]);
+
+impl Columns {
+    /// The reordering that leaves every column where it was.
+    pub const IDENTITY: Self = Self([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+    /// Follow this reordering with `next`, made afterwards.
+    pub fn then(self, next: Columns) -> Columns {
+        Columns(next.0.map(|i| self.0[i as usize]))
+    }
+
+    /// The same move made on the board as it was before the reordering.
+    pub fn map_move(&self, mv: Move) -> Move {
+        let map = |loc| match loc {
+            MoveLoc::Tableau(i) => MoveLoc::Tableau(self.0[i as usize]),
+            MoveLoc::Freecell => MoveLoc::Freecell,
+        };
+        Move { src: map(mv.src), dst: map(mv.dst) }
+    }
+}
+
+impl Board {
+    /// Put the tableau columns in a fixed order, by top card with empty columns last, so every board with the same
+    /// state has the same info too. Returns where each column came from, to map moves back to the original columns.
+    pub fn canonicalize(&mut self) -> Columns {
+        let mut order = Columns::IDENTITY.0;
+        order.sort_by_key(|&i| self.info.tableau[i as usize]);
+        self.info.tableau = order.map(|i| self.info.tableau[i as usize]);
+        Columns(order)
+    }
+}
+
+/// Translate `moves`, made from `start` with [`Board::canonicalize`] after the forced moves and after every move,
+/// back into moves on `start`'s own columns.
+pub fn physical_moves(mut start: Board, moves: &[Move]) -> Vec<Move> {
+    start.apply_forced();
+    let mut columns = start.canonicalize();
+    moves.iter().map(|mv| {
+        let physical = columns.map_move(*mv);
+        start.apply_move(*mv);
+        columns = columns.then(start.canonicalize());
+        physical
+    }).collect()
+}
+
+impl Board {
+    /// Whether every card has been scored to a foundation.
+    pub fn is_won(&self) -> bool {
+        self.state.cards.iter().all(|c| matches!(c.info(), Foundation | DownFoundn))
+    }
+}
+
 impl From<BoardState> for Board {
     fn from(value: BoardState) -> Self {
-        Board { state: value, info: value.into() }
+        Board::new(value, value.into())
     }
 }
 
@@ -361,25 +505,29 @@ mod board_tests {
 
 impl Board {
 
+    /// Play every forced move to the foundations, until none remain.
     pub fn apply_forced(&mut self) {
+        self.force(None);
+        if cfg!(debug_assertions) { self.validate() }
+    }
+
+    /// Forced moves, recording each scored card and what it was sitting on into `log` if given.
+    fn force(&mut self, mut log: Option<&mut Vec<(C, C)>>) {
         #![allow(unused_labels)]
 
         let mut done = false;
         'doneloop: while ! done {
             done = true;
 
+            // a card in the freecell covers up the minor foundations
             let suits: &[Suit] = if self.info.freecell == C::FREECELL {
-                &[Magic]
-            } else {
                 &[Wands, Stars, Swrds, Cuups, Magic]
-            };
-
-            let srcs: Box<dyn Iterator<Item = &mut C>> = if self.info.freecell == C::FREECELL {
-                Box::new(self.info.tableau.iter_mut().chain(once(&mut self.info.freecell)))
             } else {
-                Box::new(self.info.tableau.iter_mut())
+                &[Magic]
             };
 
+            let srcs = self.info.tableau.iter_mut().chain(once(&mut self.info.freecell));
+
             'srcloop: for src in srcs {
 
                 // Up Foundations
@@ -406,6 +554,9 @@ impl Board {
                             let card = *src;
                             *src = self.state.cards[card.0 as usize];
                             *dst = card;
+                            if let Some(log) = log.as_deref_mut() { log.push((card, *src)) }
+                            self.zobrist ^= zobrist_key(card, *src) ^ zobrist_key(card, C::FOUNDATION);
+                            self.state.cards[card.0 as usize] = C::FOUNDATION;
                             done = false;
                             continue 'optloop;
                         } else {
@@ -440,6 +591,9 @@ impl Board {
                             let card = *src;
                             *src = self.state.cards[card.0 as usize];
                             *dst = card;
+                            if let Some(log) = log.as_deref_mut() { log.push((card, *src)) }
+                            self.zobrist ^= zobrist_key(card, *src) ^ zobrist_key(card, C::DOWNFOUNDN);
+                            self.state.cards[card.0 as usize] = C::DOWNFOUNDN;
                             done = false;
                             continue 'optloop;
                         } else {
@@ -468,3 +622,356 @@ pub struct Move {
     pub dst: MoveLoc,
 }
 
+
+/// Upper bound on the number of player moves available from any position.
+pub const MAX_MOVES: usize = 12 * 11;
+
+/// Fixed-capacity list of moves, so move generation doesn't allocate at every search node.
+#[derive(Debug, Clone, Copy)]
+pub struct MoveList {
+    len: usize,
+    moves: [Move; MAX_MOVES],
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        let filler = Move { src: MoveLoc::Freecell, dst: MoveLoc::Freecell };
+        Self { len: 0, moves: [filler; MAX_MOVES] }
+    }
+}
+impl MoveList {
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+}
+impl Deref for MoveList {
+    type Target = [Move];
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl MoveLoc {
+    /// Every location a player move can start or end at.
+    pub fn all() -> impl Iterator<Item = MoveLoc> + Clone {
+        (0..11).map(MoveLoc::Tableau).chain(once(MoveLoc::Freecell))
+    }
+}
+
+impl Board {
+    /// The card on top of a location, or the empty marker for that location.
+    pub fn top(&self, loc: MoveLoc) -> C {
+        match loc {
+            MoveLoc::Tableau(i) => self.info.tableau[i as usize],
+            MoveLoc::Freecell => self.info.freecell,
+        }
+    }
+
+    /// Whether `card` may be put down at `dst`.
+    pub fn accepts(&self, dst: MoveLoc, card: C) -> bool {
+        match dst {
+            MoveLoc::Tableau(i) => card.stacks_on(self.info.tableau[i as usize]),
+            MoveLoc::Freecell => self.info.freecell == C::FREECELL,
+        }
+    }
+
+    /// Every legal player move from this position.
+    pub fn legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::default();
+        for src in MoveLoc::all() {
+            let card = self.top(src);
+            if ! card.info().is_card() { continue }
+            for dst in MoveLoc::all() {
+                if src != dst && self.accepts(dst, card) {
+                    moves.push(Move { src, dst });
+                }
+            }
+        }
+        moves
+    }
+}
+
+/// Everything needed to take back a move made with [`Board::apply_move`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undo {
+    /// Auxiliary state from before the move, which also covers any forced moves that followed it.
+    info: BoardInfo,
+    /// Hash from before the move.
+    zobrist: u64,
+    /// The card that was moved.
+    card: C,
+    /// What the moved card was sitting on.
+    under: C,
+    /// Each card scored by the forced moves that followed, and what it was sitting on.
+    scored: Vec<(C, C)>,
+}
+
+impl Undo {
+    /// The card that was moved.
+    pub fn card(&self) -> C {
+        self.card
+    }
+
+    /// What the moved card was sitting on before the move.
+    pub fn under(&self) -> C {
+        self.under
+    }
+
+    /// The cards that the forced moves after this move scored, in the order they were scored.
+    pub fn scored(&self) -> impl Iterator<Item = C> + '_ {
+        self.scored.iter().map(|(card, _)| *card)
+    }
+}
+
+impl Board {
+    /// Make a player move, followed by any forced moves it enables.
+    pub fn apply_move(&mut self, mv: Move) -> Undo {
+        let card = self.top(mv.src);
+        debug_assert!(card.info().is_card(), "moving from empty {:?}", mv.src);
+        debug_assert!(self.accepts(mv.dst, card), "illegal move {:?}", mv);
+
+        let mut undo = Undo { info: self.info, zobrist: self.zobrist, card, under: self.state.cards[card.0 as usize], scored: Vec::new() };
+
+        match mv.src {
+            MoveLoc::Tableau(i) => self.info.tableau[i as usize] = undo.under,
+            MoveLoc::Freecell => self.info.freecell = C::FREECELL,
+        }
+        match mv.dst {
+            MoveLoc::Tableau(i) => {
+                self.place(card, self.info.tableau[i as usize]);
+                self.info.tableau[i as usize] = card;
+            },
+            MoveLoc::Freecell => {
+                self.place(card, C::FREECELL);
+                self.info.freecell = card;
+            },
+        }
+
+        self.force(Some(&mut undo.scored));
+        if cfg!(debug_assertions) { self.validate() }
+        undo
+    }
+
+    /// Take back a move made with [`Board::apply_move`], restoring the exact prior board.
+    pub fn undo_move(&mut self, undo: Undo) {
+        for (card, under) in undo.scored.into_iter().rev() {
+            self.state.cards[card.0 as usize] = under;
+        }
+        self.state.cards[undo.card.0 as usize] = undo.under;
+        self.info = undo.info;
+        self.zobrist = undo.zobrist;
+        if cfg!(debug_assertions) { self.validate() }
+    }
+}
+
+#[cfg(test)]
+mod move_tests {
+    use super::*;
+    use crate::text::fixtures::*;
+
+    #[test]
+    fn stacking_rule() {
+        let c = |s: &str| -> C { s.parse().unwrap() };
+        assert!(c("7*").stacks_on(c("8*")));
+        assert!(c("7*").stacks_on(c("6*")));
+        assert!(!c("7*").stacks_on(c("7*")));
+        assert!(!c("7*").stacks_on(c("8!")));
+        assert!(c("11").stacks_on(c("12")));
+        assert!(c("K$").stacks_on(C::TABLEAU));
+        assert!(!c("K$").stacks_on(C::FREECELL));
+    }
+
+    #[test]
+    fn no_moves_when_won() {
+        assert_eq!(Board::default().legal_moves().len(), 0);
+    }
+
+    #[test]
+    fn fresh_moves() {
+        let board = board(FRESH);
+        let moves = board.legal_moves();
+
+        // 10 columns to the freecell, 10 to the empty column, plus 11<->12, 10<->11 and 8!<->9!
+        assert_eq!(moves.len(), 10 + 10 + 6);
+        assert!(moves.iter().all(|m| m.src != MoveLoc::Freecell));
+        assert!(moves.contains(&Move { src: MoveLoc::Tableau(2), dst: MoveLoc::Tableau(6) }));
+        assert!(moves.contains(&Move { src: MoveLoc::Tableau(4), dst: MoveLoc::Tableau(9) }));
+        assert!(!moves.contains(&Move { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(1) }));
+    }
+
+    #[test]
+    fn freecell_moves() {
+        let mut board = board(FRESH);
+        let card = board.info.tableau[9];
+        board.info.tableau[9] = board.state.cards[card.0 as usize];
+        board.state.cards[card.0 as usize] = C::FREECELL;
+        board.info.freecell = card;
+        board.rehash();
+        let moves = board.legal_moves();
+
+        // 9! can go onto 8! or into the empty column, and nothing else fits in the occupied freecell
+        assert!(moves.iter().all(|m| m.dst != MoveLoc::Freecell));
+        assert_eq!(moves.iter().filter(|m| m.src == MoveLoc::Freecell).count(), 2);
+    }
+
+    #[test]
+    fn apply_undo_roundtrip() {
+        let mut board = board(FRESH);
+        let start = board;
+
+        for mv in &board.legal_moves() {
+            let undo = board.apply_move(*mv);
+            assert_ne!(board.state, start.state);
+
+            let before = board;
+            for mv2 in &board.legal_moves() {
+                let undo2 = board.apply_move(*mv2);
+                board.undo_move(undo2);
+                assert_eq!(board.state, before.state);
+                assert_eq!(board.info, before.info);
+            }
+
+            board.undo_move(undo);
+            assert_eq!(board.state, start.state);
+            assert_eq!(board.info, start.info);
+        }
+    }
+
+    #[test]
+    fn canonicalize_collapses_column_order() {
+        let mut board = board(FRESH);
+        board.apply_forced();
+        let physical = board;
+        let columns = board.canonicalize();
+
+        assert!(board.info.tableau.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(board.info, BoardInfo::from(board.state));
+        assert_eq!(board.canonicalize(), Columns::IDENTITY);
+        for (slot, col) in columns.0.iter().enumerate() {
+            assert_eq!(board.info.tableau[slot], physical.info.tableau[*col as usize]);
+        }
+
+        // the same position reached with the columns in another order canonicalizes the same way
+        let mut swapped = physical;
+        swapped.info.tableau.swap(0, 3);
+        swapped.canonicalize();
+        assert_eq!(swapped.info, board.info);
+    }
+
+    #[test]
+    fn physical_moves_follow_columns() {
+        let start = board(FRESH);
+        let mut canon = start;
+        canon.apply_forced();
+        canon.canonicalize();
+        let mut moves = Vec::new();
+        for _ in 0..20 {
+            let Some(&mv) = canon.legal_moves().first() else { break };
+            canon.apply_move(mv);
+            canon.canonicalize();
+            moves.push(mv);
+        }
+
+        let mut board = start;
+        board.apply_forced();
+        for mv in physical_moves(start, &moves) {
+            assert!(board.legal_moves().contains(&mv));
+            board.apply_move(mv);
+        }
+        assert_eq!(board.state, canon.state);
+    }
+
+    #[test]
+    fn zobrist_tracks_moves() {
+        let mut board = board(FRESH);
+        board.apply_forced();
+        let start = board.zobrist();
+        assert_eq!(start, board.state.zobrist());
+
+        let mut undos = Vec::new();
+        let mut seen = std::collections::HashSet::from([start]);
+        for _ in 0..30 {
+            let Some(&mv) = board.legal_moves().last() else { break };
+            undos.push(board.apply_move(mv));
+            assert_eq!(board.zobrist(), board.state.zobrist());
+            seen.insert(board.zobrist());
+        }
+        for undo in undos.into_iter().rev() {
+            board.undo_move(undo);
+            assert_eq!(board.zobrist(), board.state.zobrist());
+        }
+        assert_eq!(board.zobrist(), start);
+        assert!(seen.len() > 2, "moves should change the hash");
+    }
+
+    #[test]
+    fn apply_moves_card() {
+        let mut board = board(FRESH);
+        let card = board.info.tableau[9];
+        let under = board.state.cards[card.0 as usize];
+
+        board.apply_move(Move { src: MoveLoc::Tableau(9), dst: MoveLoc::Freecell });
+        assert_eq!(board.info.freecell, card);
+        assert_eq!(board.info.tableau[9], under);
+        assert_eq!(board.state.cards[card.0 as usize], C::FREECELL);
+
+        board.apply_move(Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(4) });
+        assert_eq!(board.info.freecell, C::FREECELL);
+        assert_eq!(board.state.cards[card.0 as usize], "8!".parse().unwrap());
+        assert_eq!(board.info.tableau[4], card);
+    }
+
+    #[test]
+    fn forced_moves_update_state() {
+        let c = |s: &str| -> C { s.parse().unwrap() };
+        let mut board = board(FRESH);
+        let start = board;
+
+        // exposes 00, which scores and exposes 01, but the occupied freecell holds back 2!
+        let undo1 = board.apply_move(Move { src: MoveLoc::Tableau(4), dst: MoveLoc::Freecell });
+        assert_eq!(board.state.cards[c("00").0 as usize], C::FOUNDATION);
+        assert_eq!(board.state.cards[c("01").0 as usize], C::FOUNDATION);
+        assert_eq!(board.info.foundation[Magic as usize], c("01"));
+        assert_eq!(board.info.tableau[1], c("2!"));
+        let after1 = board;
+
+        // emptying the freecell lets 2! score
+        let undo2 = board.apply_move(Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(9) });
+        assert_eq!(board.state.cards[c("2!").0 as usize], C::FOUNDATION);
+        assert_eq!(board.info.foundation[Swrds as usize], c("2!"));
+        board.validate();
+
+        board.undo_move(undo2);
+        assert_eq!(board.state, after1.state);
+        assert_eq!(board.info, after1.info);
+        board.undo_move(undo1);
+        assert_eq!(board.state, start.state);
+        assert_eq!(board.info, start.info);
+    }
+
+    #[test]
+    fn forced_down_foundation() {
+        let layout = "10 K|\n21 Q|\n11 12 13 14 15 16 17 18 19 20\n\n\n\n\n\n\n\n\n";
+        let mut board = board(layout);
+        board.apply_forced();
+        assert!(board.state.cards.iter().all(|c| matches!(c.info(), Foundation | DownFoundn)));
+        assert_eq!(board.state.cards[C::MAGIC_WORLD.0 as usize], C::DOWNFOUNDN);
+        assert_eq!(board.state.cards[C::MAGIC_FOOL.0 as usize + 10], C::FOUNDATION);
+    }
+
+    #[test]
+    fn info_from_state() {
+        let board = board(FRESH);
+        board.validate();
+        let canon: Board = board.state.into();
+        assert_eq!(canon.info.tableau.iter().filter(|t| **t == C::TABLEAU).count(), 1);
+    }
+}
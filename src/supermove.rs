@@ -0,0 +1,328 @@
+//! Moves of a whole run of cards at once, as the game lets a player drag them when there's room to shuffle.
+//!
+//! A run is a stretch of same-suit cards at the top of a column, each one rank away from the card below it. With the
+//! freecell and empty columns to park cards in, a run moves as a unit by the usual shuffle: half onto a spare empty
+//! column, the rest across, then the first half back on top. [`expand`] spells that out as single-card [`Move`]s.
+use crate::state::*;
+
+/// Move the top `count` cards of `src` to `dst` together, keeping their order.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StackMove {
+    pub src: MoveLoc,
+    pub dst: MoveLoc,
+    pub count: u8,
+}
+
+impl From<Move> for StackMove {
+    fn from(mv: Move) -> Self {
+        StackMove { src: mv.src, dst: mv.dst, count: 1 }
+    }
+}
+
+/// Most cards that can move together given the free space in `info`, not counting `dst` if it's an empty column.
+///
+/// Each empty column doubles what the freecell alone allows, as in classic FreeCell.
+pub fn max_run(info: &BoardInfo, dst: MoveLoc) -> usize {
+    let free = (info.freecell == C::FREECELL) as usize;
+    let empty = info.tableau.iter().enumerate()
+        .filter(|(i, top)| **top == C::TABLEAU && dst != MoveLoc::Tableau(*i as u8))
+        .count();
+    (free + 1) << empty
+}
+
+/// The cards of the run at the top of column `col`, top first.
+pub fn top_run(board: &Board, col: u8) -> Vec<C> {
+    let mut run = Vec::new();
+    let mut card = board.info.tableau[col as usize];
+    while card.info().is_card() {
+        run.push(card);
+        let under = board.state.cards[card.0 as usize];
+        if !card.stacks_on(under) || under == C::TABLEAU { break }
+        card = under;
+    }
+    run
+}
+
+/// Every stack move from `board`: each legal single-card move, plus each run of two or more cards that can move
+/// onto a card or into an empty column and plays out as planned.
+///
+/// Runs go only to the first empty column, and never move a whole column into an empty one, since neither extra
+/// choice reaches a position the others don't.
+pub fn stack_moves(board: &Board) -> Vec<StackMove> {
+    let first_empty = (0..11).find(|i| board.info.tableau[*i as usize] == C::TABLEAU).map(MoveLoc::Tableau);
+    let mut moves: Vec<StackMove> = board.legal_moves().iter().map(|mv| StackMove::from(*mv)).collect();
+    moves.extend(run_moves(board).into_iter().filter(|(sm, whole_column)| {
+        board.top(sm.dst) != C::TABLEAU || (Some(sm.dst) == first_empty && !whole_column)
+    }).map(|(sm, _)| sm));
+    moves
+}
+
+/// Every move of two or more cards that [`expand`] can carry out, with whether it takes a whole column.
+fn run_moves(board: &Board) -> Vec<(StackMove, bool)> {
+    let mut moves = Vec::new();
+    for src in 0..11 {
+        let run = top_run(board, src);
+        let whole_column = run.last().is_some_and(|c| board.state.cards[c.0 as usize] == C::TABLEAU);
+        for dst in (0..11).filter(|dst| *dst != src) {
+            let onto = board.info.tableau[dst as usize];
+            let cap = max_run(&board.info, MoveLoc::Tableau(dst)).min(run.len());
+            for count in 2..=cap {
+                if !run[count - 1].stacks_on(onto) { continue }
+                let sm = StackMove { src: MoveLoc::Tableau(src), dst: MoveLoc::Tableau(dst), count: count as u8 };
+                if expand(board, sm).is_some() {
+                    moves.push((sm, whole_column && count == run.len()));
+                }
+            }
+        }
+    }
+    moves
+}
+
+/// Plan moving `n` cards from `src` to `dst`, using the freecell if `freecell` and the columns in `empty` as space.
+fn plan(n: usize, src: MoveLoc, dst: MoveLoc, empty: &[u8], freecell: bool, out: &mut Vec<Move>) {
+    let Some((&spare, rest)) = empty.split_first() else {
+        match n {
+            1 => out.push(Move { src, dst }),
+            2 if freecell => {
+                out.push(Move { src, dst: MoveLoc::Freecell });
+                out.push(Move { src, dst });
+                out.push(Move { src: MoveLoc::Freecell, dst });
+            },
+            _ => panic!("no room to move {} cards", n),
+        }
+        return;
+    };
+    let without = (freecell as usize + 1) << rest.len();
+    if n <= without {
+        return plan(n, src, dst, rest, freecell, out);
+    }
+    let spare = MoveLoc::Tableau(spare);
+    plan(n - without, src, spare, rest, freecell, out);
+    plan(without, src, dst, rest, freecell, out);
+    plan(n - without, spare, dst, rest, freecell, out);
+}
+
+/// The single-card moves that carry out `sm` on `board`, or `None` if it can't be done that way: there's too little
+/// room, a step isn't legal, or a forced move scores one of the run's cards partway through and breaks it up.
+/// Cards uncovered under the run may score as usual.
+pub fn expand(board: &Board, sm: StackMove) -> Option<Vec<Move>> {
+    let count = sm.count as usize;
+    if count == 0 || (count > 1 && (sm.src == MoveLoc::Freecell || sm.dst == MoveLoc::Freecell)) { return None }
+    if count > max_run(&board.info, sm.dst) { return None }
+
+    let empty: Vec<u8> = (0..11)
+        .filter(|i| board.info.tableau[*i as usize] == C::TABLEAU && sm.dst != MoveLoc::Tableau(*i))
+        .collect();
+    let mut moves = Vec::new();
+    plan(count, sm.src, sm.dst, &empty, board.info.freecell == C::FREECELL, &mut moves);
+
+    let mut run = Vec::with_capacity(count);
+    let mut card = board.top(sm.src);
+    while run.len() < count && card.info().is_card() {
+        run.push(card);
+        card = board.state.cards[card.0 as usize];
+    }
+
+    let mut replay = *board;
+    for (i, mv) in moves.iter().enumerate() {
+        let card = replay.top(mv.src);
+        if !card.info().is_card() || !replay.accepts(mv.dst, card) { return None }
+        let undo = replay.apply_move(*mv);
+        if i + 1 < moves.len() && undo.scored().any(|c| run.contains(&c)) { return None }
+    }
+    Some(moves)
+}
+
+/// Longest stretch of single-card moves considered for merging into one run move.
+const MAX_SHUFFLE: usize = 64;
+
+/// A solution rewritten with runs moved as units, for a person to play through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compressed {
+    pub moves: Vec<StackMove>,
+    /// How many single-card moves the original solution took.
+    pub raw_len: usize,
+}
+
+/// Merge stretches of `moves` that together move a run into single [`StackMove`]s, taking the longest stretch that
+/// leaves the board exactly as one run move would, columns and all. The result is replayed to check it still wins
+/// from `start`; `None` means `moves` doesn't win from `start` in the first place.
+pub fn compress(start: Board, moves: &[Move]) -> Option<Compressed> {
+    let mut board = start;
+    board.apply_forced();
+    let mut boards = vec![board];
+    for mv in moves {
+        if !board.legal_moves().contains(mv) { return None }
+        board.apply_move(*mv);
+        boards.push(board);
+    }
+    if !board.is_won() { return None }
+
+    let same = |a: &Board, b: &Board| a.state == b.state && a.info == b.info;
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < moves.len() {
+        let runs: Vec<(StackMove, Board)> = run_moves(&boards[i]).into_iter().filter_map(|(sm, _)| {
+            let mut after = boards[i];
+            for mv in expand(&after, sm)? {
+                after.apply_move(mv);
+            }
+            Some((sm, after))
+        }).collect();
+
+        let end = moves.len().min(i + MAX_SHUFFLE);
+        let merged = (i + 2..=end).rev()
+            .find_map(|j| runs.iter().find(|(_, after)| same(after, &boards[j])).map(|(sm, _)| (j, *sm)));
+        match merged {
+            Some((j, sm)) => { out.push(sm); i = j },
+            None => { out.push(moves[i].into()); i += 1 },
+        }
+    }
+
+    let mut replay = start;
+    replay.apply_forced();
+    for sm in &out {
+        for mv in expand(&replay, *sm)? {
+            replay.apply_move(mv);
+        }
+    }
+    replay.is_won().then_some(Compressed { moves: out, raw_len: moves.len() })
+}
+
+
+#[cfg(test)]
+mod supermove_tests {
+    use super::*;
+    use crate::{deal::{deal, Rng}, solve::{solve_best_first, SolveResult}, text::fixtures::*};
+
+    #[test]
+    fn run_capacity() {
+        let mut info = settled(RUNS).info;
+        assert_eq!(max_run(&info, MoveLoc::Tableau(1)), 2 << 8);
+        assert_eq!(max_run(&info, MoveLoc::Tableau(3)), 2 << 7);
+
+        info.freecell = "2*".parse().unwrap();
+        info.tableau[3..].fill(C::FOUNDATION);
+        assert_eq!(max_run(&info, MoveLoc::Tableau(1)), 1);
+        info.tableau[3] = C::TABLEAU;
+        assert_eq!(max_run(&info, MoveLoc::Tableau(1)), 2);
+        assert_eq!(max_run(&info, MoveLoc::Tableau(3)), 1);
+    }
+
+    #[test]
+    fn finds_runs() {
+        let board = settled(RUNS);
+        let c = |s: &str| s.parse::<C>().unwrap();
+        assert_eq!(top_run(&board, 0), [c("9|"), c("T|"), c("J|")]);
+        assert_eq!(top_run(&board, 2), [c("K|")]);
+        assert_eq!(top_run(&board, 3), []);
+
+        let moves = stack_moves(&board);
+        let multi: Vec<_> = moves.iter().filter(|sm| sm.count > 1).collect();
+        assert_eq!(multi, [
+            &StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(1), count: 3 },
+            &StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(3), count: 2 },
+            &StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(3), count: 3 },
+        ]);
+        assert_eq!(moves.len() - multi.len(), board.legal_moves().len());
+    }
+
+    #[test]
+    fn expands_to_single_moves() {
+        let start = settled(RUNS);
+        let sm = StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(1), count: 3 };
+        let moves = expand(&start, sm).unwrap();
+        assert_eq!(moves.len(), 5);
+
+        let mut board = start;
+        for mv in &moves {
+            board.apply_move(*mv);
+        }
+        let c = |s: &str| s.parse::<C>().unwrap();
+        assert_eq!(top_run(&board, 1), [c("9|"), c("T|"), c("J|"), c("Q|")]);
+        assert_eq!(board.info.tableau[0], c("8|"));
+        assert_eq!(board.info.freecell, C::FREECELL);
+        assert!(board.info.tableau[3..].iter().all(|top| *top == C::TABLEAU));
+
+        // uncovering 7| under the run scores it partway through, which leaves the run alone
+        let scores = settled("7| J| T| 9|\n8| Q|\nK|\n\n\n\n\n\n\n\n\n");
+        assert!(expand(&scores, sm).is_some());
+
+        // the same run with no space to shuffle in
+        assert_eq!(expand(&start, StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(2), count: 3 }), None);
+        assert_eq!(expand(&start, StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Freecell, count: 2 }), None);
+    }
+
+    #[test]
+    fn run_broken_by_scoring() {
+        // the majors run up to 04 and down to 11; parking 06 uncovers 05, which scores along with 06 from the
+        // freecell, since an occupied freecell only holds back the minor suits
+        let board = settled("07 05 06\n10 08 09\n\n\n\n\n\n\n\n\n\n");
+        let c = |s: &str| s.parse::<C>().unwrap();
+        assert_eq!(top_run(&board, 0), [c("06"), c("05")]);
+
+        let sm = StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(2), count: 2 };
+        assert_eq!(expand(&board, sm), None);
+        assert!(stack_moves(&board).iter().all(|sm| sm.count == 1));
+    }
+
+    #[test]
+    fn expansions_replay_during_play() {
+        let mut rng = Rng::new(21);
+        for seed in 0..10 {
+            let mut board: Board = deal(seed).into();
+            board.apply_forced();
+            for _ in 0..60 {
+                let moves = stack_moves(&board);
+                if moves.is_empty() { break }
+                for sm in moves.iter().filter(|sm| sm.count > 1) {
+                    let mut replay = board;
+                    let expansion = expand(&board, *sm).unwrap();
+                    assert!(expansion.len() >= sm.count as usize);
+                    for mv in expansion {
+                        assert!(replay.legal_moves().contains(&mv), "seed {} {:?}", seed, sm);
+                        replay.apply_move(mv);
+                    }
+                }
+                // favour moves onto cards, which build the runs worth testing
+                let building: Vec<_> = moves.iter().filter(|sm| board.top(sm.dst).info().is_card()).collect();
+                let sm = match building.len() {
+                    0 => moves[rng.below(moves.len() as u64) as usize],
+                    n => *building[rng.below(n as u64) as usize],
+                };
+                for mv in expand(&board, sm).unwrap() {
+                    board.apply_move(mv);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn compress_shuffles() {
+        // 9| T| J| onto Q| by hand through two empty columns, then Q| J| T| 9| one by one into four more,
+        // which uncovers 7| and lets every wand score; the second stretch can't be a single run move, as parking
+        // 9| on its own partway through would score it
+        let start = settled(RUNS);
+        let mv = |src, dst| Move { src: MoveLoc::Tableau(src), dst: MoveLoc::Tableau(dst) };
+        let moves = [mv(0, 5), mv(0, 4), mv(0, 1), mv(4, 1), mv(5, 1), mv(1, 3), mv(1, 4), mv(1, 5), mv(1, 6)];
+
+        let compressed = compress(start, &moves).expect("solution should still win");
+        assert_eq!(compressed.raw_len, moves.len());
+        assert_eq!(compressed.moves[0], StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(1), count: 3 });
+        assert!(compressed.moves[1..].iter().all(|sm| sm.count < 4));
+        assert!(compressed.moves.len() < moves.len() - 4);
+
+        assert_eq!(compress(start, &moves[..5]), None);
+    }
+
+    #[test]
+    fn compress_solver_output() {
+        for seed in [9, 10] {
+            let start: Board = deal(seed).into();
+            let SolveResult::Solved(moves) = solve_best_first(start) else { panic!("seed {}", seed) };
+            let compressed = compress(start, &moves).unwrap();
+            assert_eq!(compressed.raw_len, moves.len());
+            assert!(compressed.moves.len() <= moves.len());
+        }
+    }
+}
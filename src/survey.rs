@@ -0,0 +1,132 @@
+//! Solving runs of seeded deals in parallel, to measure how many can be won and how hard they are.
+//! 
+//! 
+use std::{ops::Range, sync::{atomic::{AtomicU64, Ordering}, mpsc}, thread};
+use crate::deal::deal;
+use crate::solve::*;
+use crate::state::*;
+
+/// How the search for one deal ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Solved,
+    Unsolvable,
+    LimitReached,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Self::Solved => "solved",
+            Self::Unsolvable => "unsolvable",
+            Self::LimitReached => "limit",
+        })
+    }
+}
+
+/// One deal's line of the survey.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Row {
+    pub seed: u64,
+    pub verdict: Verdict,
+    /// Number of player moves in the solution, if one was found.
+    pub moves: Option<usize>,
+    pub stats: SearchStats,
+}
+
+impl Row {
+    pub const CSV_HEADER: &'static str = "seed,result,moves,nodes,peak_bytes,pruned_no_op,pruned_symmetric,pruned_reversal";
+
+    pub fn to_csv(&self) -> String {
+        let moves = self.moves.map_or(String::new(), |m| m.to_string());
+        let pruned = &self.stats.pruned;
+        format!("{},{},{},{},{},{},{},{}", self.seed, self.verdict, moves, self.stats.nodes_expanded, self.stats.peak_bytes,
+            pruned.no_op, pruned.symmetric, pruned.reversal)
+    }
+
+    pub fn to_json(&self) -> String {
+        let moves = self.moves.map_or("null".to_string(), |m| m.to_string());
+        let pruned = &self.stats.pruned;
+        format!(r#"{{"seed":{},"result":"{}","moves":{},"nodes":{},"peak_bytes":{},"pruned":{{"no_op":{},"symmetric":{},"reversal":{}}}}}"#,
+            self.seed, self.verdict, moves, self.stats.nodes_expanded, self.stats.peak_bytes,
+            pruned.no_op, pruned.symmetric, pruned.reversal)
+    }
+}
+
+/// Deal and solve one seed.
+pub fn survey_one(seed: u64, config: &SolveConfig) -> Row {
+    let board: Board = deal(seed).into();
+    let (result, stats) = solve_best_first_with(board, config);
+    let (verdict, moves) = match result {
+        SolveResult::Solved(moves) => (Verdict::Solved, Some(moves.len())),
+        SolveResult::Unsolvable { .. } => (Verdict::Unsolvable, None),
+        SolveResult::LimitReached { .. } => (Verdict::LimitReached, None),
+    };
+    Row { seed, verdict, moves, stats }
+}
+
+/// Solve every seed in `seeds` across `threads` worker threads, handing each row to `emit` as it finishes.
+///
+/// Rows arrive in the order deals finish rather than by seed.
+pub fn survey(seeds: Range<u64>, config: &SolveConfig, threads: usize, mut emit: impl FnMut(Row)) {
+    let next = AtomicU64::new(seeds.start);
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let tx = tx.clone();
+            let next = &next;
+            let end = seeds.end;
+            scope.spawn(move || loop {
+                let seed = next.fetch_add(1, Ordering::Relaxed);
+                if seed >= end { break }
+                if tx.send(survey_one(seed, config)).is_err() { break }
+            });
+        }
+        drop(tx);
+
+        for row in rx {
+            emit(row);
+        }
+    });
+}
+
+
+#[cfg(test)]
+mod survey_tests {
+    use super::*;
+    use crate::prune::Pruned;
+
+    #[test]
+    fn one_row_per_seed() {
+        let config = SolveConfig { max_nodes: Some(200), ..Default::default() };
+        let mut rows = Vec::new();
+        survey(10..16, &config, 3, |row| rows.push(row));
+        rows.sort_by_key(|row| row.seed);
+
+        assert_eq!(rows.iter().map(|row| row.seed).collect::<Vec<_>>(), (10..16).collect::<Vec<_>>());
+        for row in &rows {
+            // everything but the time taken comes out the same on a second run
+            let mut again = survey_one(row.seed, &config);
+            again.stats.elapsed = row.stats.elapsed;
+            assert_eq!(*row, again);
+            assert_eq!(row.moves.is_some(), row.verdict == Verdict::Solved);
+            assert!(row.stats.nodes_expanded <= 200);
+        }
+    }
+
+    #[test]
+    fn row_formats() {
+        let pruned = Pruned { no_op: 1, symmetric: 2, reversal: 3 };
+        let stats = SearchStats { nodes_expanded: 12, peak_bytes: 3456, pruned, ..Default::default() };
+        let row = Row { seed: 7, verdict: Verdict::Solved, moves: Some(80), stats };
+        assert_eq!(row.to_csv(), "7,solved,80,12,3456,1,2,3");
+        assert_eq!(row.to_json(),
+            r#"{"seed":7,"result":"solved","moves":80,"nodes":12,"peak_bytes":3456,"pruned":{"no_op":1,"symmetric":2,"reversal":3}}"#);
+
+        let row = Row { seed: 8, verdict: Verdict::LimitReached, moves: None, stats };
+        assert_eq!(row.to_csv(), "8,limit,,12,3456,1,2,3");
+        assert_eq!(row.to_json(),
+            r#"{"seed":8,"result":"limit","moves":null,"nodes":12,"peak_bytes":3456,"pruned":{"no_op":1,"symmetric":2,"reversal":3}}"#);
+    }
+}
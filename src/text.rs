@@ -3,6 +3,79 @@
 //! 
 use std::{collections::HashSet, fmt, iter::zip, str::FromStr};
 use crate::state::*;
+use crate::supermove::{expand, StackMove};
+
+/// 1-based line and column of a token within a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pos {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Why a suit, card or board couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The token contains symbols for more than one suit.
+    AmbiguousSuit { token: String, at: Option<Pos> },
+    /// The token has no rank, or more than one.
+    BadRank { token: String, at: Option<Pos> },
+    /// The rank isn't one of the cards in play for the suit.
+    RankOutOfRange { token: String, at: Option<Pos> },
+    /// The token is a marker like `--` or `FF` rather than a card.
+    NotACard { token: String, at: Option<Pos> },
+    /// The card was already listed elsewhere in the tableau.
+    DuplicateCard { token: String, at: Option<Pos> },
+    /// A board needs exactly 11 tableau lines.
+    RowCount { rows: usize },
+    /// The cards missing from the tableau aren't a run up each foundation plus at most one freecell card.
+    ImpossibleFoundation { missing: Vec<C> },
+    /// The line isn't a move like `7* > 8*`, `9| > FF` or `J! > --`.
+    BadMove { token: String, at: Option<Pos> },
+}
+
+impl ParseError {
+    /// Attach the position of the offending token.
+    fn at(self, pos: Pos) -> Self {
+        use ParseError::*;
+        match self {
+            AmbiguousSuit { token, .. } => AmbiguousSuit { token, at: Some(pos) },
+            BadRank { token, .. } => BadRank { token, at: Some(pos) },
+            RankOutOfRange { token, .. } => RankOutOfRange { token, at: Some(pos) },
+            NotACard { token, .. } => NotACard { token, at: Some(pos) },
+            DuplicateCard { token, .. } => DuplicateCard { token, at: Some(pos) },
+            BadMove { token, .. } => BadMove { token, at: Some(pos) },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ParseError::*;
+        let (token, at, reason) = match self {
+            AmbiguousSuit { token, at } => (token, at, "names more than one suit"),
+            BadRank { token, at } => (token, at, "has no single rank"),
+            RankOutOfRange { token, at } => (token, at, "has a rank that isn't in play for its suit"),
+            NotACard { token, at } => (token, at, "is not a card"),
+            DuplicateCard { token, at } => (token, at, "appears more than once"),
+            BadMove { token, at } => (token, at, "is not a move like \"7* > 8*\", \"9| > FF\" or \"J! > --\""),
+            RowCount { rows } => return write!(f, "expected 11 tableau lines, found {}", rows),
+            ImpossibleFoundation { missing } => {
+                write!(f, "cards missing from the tableau can't all be in the foundations or freecell:")?;
+                for card in missing {
+                    write!(f, " {}", card)?;
+                }
+                return Ok(());
+            },
+        };
+        if let Some(pos) = at {
+            write!(f, "line {}, column {}: ", pos.line, pos.column)?;
+        }
+        write!(f, "{:?} {}", token, reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
 
 #[cfg(test)]
 mod suit_tests {
@@ -116,10 +189,11 @@ impl fmt::Display for Suit {
     }
 }
 impl FromStr for Suit {
-    type Err = ();
+    type Err = ParseError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_uppercase();
+    #[allow(clippy::suspicious_else_formatting)]
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let s = token.to_uppercase();
         let is_wands = s.contains("|") || s.contains("W");
         let is_stars = s.contains("*") || s.contains("P");
         let is_swrds = s.contains("!") || s.contains("X");
@@ -137,7 +211,7 @@ impl FromStr for Suit {
                 if is_magic { Ok(Self::Magic) } else 
                 { panic!("should be impossible") }
             },
-            _ => Err(()),
+            _ => Err(ParseError::AmbiguousSuit { token: token.to_string(), at: None }),
         }
     }
 }
@@ -190,6 +264,8 @@ mod card_tests {
     #[test]
     fn display() {
         check_format!("{}");
+        check_format!("{:#}", |_card: C, s: &String| s.len()==2, "not length 2");
+        assert_eq!(format!("{:#} {:#} {:#}", "*7".parse::<C>().unwrap(), "12".parse::<C>().unwrap(), C::FREECELL), "7* 12 FF");
     }
 
     #[test]
@@ -256,8 +332,15 @@ impl fmt::UpperExp for C {
     }
 }
 impl fmt::Display for C {
+    /// The short form, suit then rank. The alternate form puts a minor card's rank first, as in `7*`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::LowerHex::fmt(self, f)
+        let info = self.info();
+        if f.alternate() && info.is_card() && info.card_suit().is_minor() {
+            fmt_shortrank(f, info.card_rank())?;
+            write!(f, "{:x}", info.card_suit())
+        } else {
+            fmt::LowerHex::fmt(self, f)
+        }
     }
 }
 fn fmt_shortrank(f: &mut fmt::Formatter<'_>, rank: u8) -> fmt::Result {
@@ -270,6 +353,7 @@ fn fmt_shortrank(f: &mut fmt::Formatter<'_>, rank: u8) -> fmt::Result {
         _ => panic!("no shortrank repr for {}", rank)
     }
 }
+#[allow(clippy::suspicious_else_formatting)]
 fn from_shortrank_special(s: &str) -> Result<u8,()> {
     let s = s.to_uppercase();
     let is_ace   = s.contains("A");
@@ -306,6 +390,7 @@ fn fmt_noncard(f: &mut fmt::Formatter<'_>, noncard: C) -> fmt::Result {
         _ => panic!("not a noncard"),
     }
 }
+#[allow(clippy::suspicious_else_formatting)]
 fn from_noncard(s: &str) -> Result<C,()> {
     let s = s.to_uppercase();
     let is_tab   = s.contains("-");
@@ -329,82 +414,80 @@ fn from_noncard(s: &str) -> Result<C,()> {
 }
 
 impl FromStr for C {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         from_noncard(s).or_else(|_|{
             let suit: Suit = s.parse()?;
-            let rank = from_shortrank_special(s).or_else(|_|from_rank(s))?;
+            let rank = from_shortrank_special(s).or_else(|_|from_rank(s))
+                .map_err(|_| ParseError::BadRank { token: s.to_string(), at: None })?;
+            let in_play = if suit.is_minor() { (2..=13).contains(&rank) } else { rank <= 21 };
+            if !in_play { return Err(ParseError::RankOutOfRange { token: s.to_string(), at: None }) }
             Ok(CardInfo::Card(suit, rank).pack())
         })
     }
 }
 
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct BoardLayout {
-    tableau: [Vec<C>; 11],
-    info: BoardInfo,
+/// Physical layout of a board, with each tableau column listed bottom-to-top in on-screen order.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BoardLayout {
+    pub tableau: [Vec<C>; 11],
+    pub info: BoardInfo,
 }
 
-impl Default for BoardLayout {
-    fn default() -> Self {
-        Self { tableau: Default::default(), info: Default::default() }
-    }
-}
+impl From<Board> for BoardLayout {
+    fn from(value: Board) -> Self {
+        let mut result = BoardLayout { tableau: Default::default(), info: value.info };
 
-impl From<BoardState> for BoardLayout {
-    fn from(value: BoardState) -> Self {
-        let mut done = false;
-        let mut result = BoardLayout { tableau: Default::default(), info: value.into() };
-        while !done {
-            done = true;
-
-            for (i, card_state) in value.cards.iter().enumerate() {
-                let card = C(i as u8);
-                for tab in result.tableau.iter_mut() {
-                    let top = *tab.last().unwrap_or(&C::TABLEAU);
-                    if *card_state == top {
-                        tab.push(card);
-                        done = false;
-                    }
-                }
+        // walk each stack down from its top card, then flip it to list bottom-to-top
+        for (tab, tab_top) in zip(result.tableau.iter_mut(), value.info.tableau.iter()) {
+            let mut card = *tab_top;
+            while card.info().is_card() {
+                tab.push(card);
+                card = value.state.cards[card.0 as usize];
             }
-        }
-
-        for (tab, tab_top) in zip(result.tableau.iter(), result.info.tableau.iter()) {
-            let tab_end = tab.last().unwrap_or(&C::TABLEAU);
-            assert_eq!(tab_end, tab_top);
+            tab.reverse();
         }
 
         result
     }
 }
 
-impl Into<Board> for BoardLayout {
-    fn into(self) -> Board {
-        let mut result = Board { state: Default::default(), info: self.info };
+impl From<BoardState> for BoardLayout {
+    fn from(value: BoardState) -> Self {
+        Board::from(value).into()
+    }
+}
+
+impl From<BoardLayout> for Board {
+    fn from(value: BoardLayout) -> Self {
+        let mut state = BoardState::default();
 
         // mark each card in the tableau with the card below it, or tableau for bottom-cards
-        for tab in self.tableau.iter() {
-            result.state.cards[tab[0].0 as usize] = C::TABLEAU;
+        for tab in value.tableau.iter() {
+            if let Some(bottom) = tab.first() {
+                state.cards[bottom.0 as usize] = C::TABLEAU;
+            }
             for w in tab.windows(2) {
-                result.state.cards[w[1].0 as usize] = w[0];
+                state.cards[w[1].0 as usize] = w[0];
             }
         }
 
         // mark the card in the freecell
-        result.state.cards[self.info.freecell.0 as usize] = C::FREECELL;
+        if value.info.freecell.info().is_card() {
+            state.cards[value.info.freecell.0 as usize] = C::FREECELL;
+        }
 
         // mark all minor arcana cards from 2 to top
         for suit in [Suit::Wands, Suit::Stars, Suit::Swrds, Suit::Cuups] {
-            let top = self.info.foundation[suit as usize];
+            let top = value.info.foundation[suit as usize];
             let ti = top.info();
             if ti.is_card() {
                 assert_eq!(ti.card_suit(), suit);
                 for rank in 2..=ti.card_rank() {
                     let ci = CardInfo::Card(ti.card_suit(), rank);
-                    result.state.cards[ci.pack().0 as usize] = C::FOUNDATION;
+                    state.cards[ci.pack().0 as usize] = C::FOUNDATION;
                 }
             }
         }
@@ -412,13 +495,13 @@ impl Into<Board> for BoardLayout {
         // mark all major arcana cards from Fool to top
         {
             let suit = Suit::Magic;
-            let top = self.info.foundation[suit as usize];
+            let top = value.info.foundation[suit as usize];
             let ti = top.info();
             if ti.is_card() {
                 assert_eq!(ti.card_suit(), suit);
                 for rank in 0..=ti.card_rank() {
                     let ci = CardInfo::Card(ti.card_suit(), rank);
-                    result.state.cards[ci.pack().0 as usize] = C::FOUNDATION;
+                    state.cards[ci.pack().0 as usize] = C::FOUNDATION;
                 }
             }
         }
@@ -426,18 +509,18 @@ impl Into<Board> for BoardLayout {
         // mark all major arcana cards from top to World
         {
             let suit = Suit::Magic;
-            let top: C = self.info.down_foundn;
+            let top: C = value.info.down_foundn;
             let ti = top.info();
             if ti.is_card() {
                 assert_eq!(ti.card_suit(), suit);
                 for rank in ti.card_rank()..=21 {
                     let ci = CardInfo::Card(ti.card_suit(), rank);
-                    result.state.cards[ci.pack().0 as usize] = C::DOWNFOUNDN;
+                    state.cards[ci.pack().0 as usize] = C::DOWNFOUNDN;
                 }
             }
         }
 
-        result
+        Board::new(state, value.info)
     }
 }
 
@@ -448,8 +531,10 @@ impl Into<Board> for BoardLayout {
 // - a minor arcana card can't become scorable while in the freecell because the next-lower card can't be scored
 // - it can't be scorable when being moved to the freecell because if it's free to move it would be scored immediately instead
 
-impl From<[Vec<C>; 11]> for BoardLayout {
-    fn from(value: [Vec<C>; 11]) -> Self {
+impl TryFrom<[Vec<C>; 11]> for BoardLayout {
+    type Error = ParseError;
+
+    fn try_from(value: [Vec<C>; 11]) -> Result<Self, Self::Error> {
         let all_cards: HashSet<C> = {
             let mut result: HashSet<C> = Default::default();
             for i in CARDS_BASE..=CARDS_HIGH {
@@ -462,13 +547,18 @@ impl From<[Vec<C>; 11]> for BoardLayout {
             let mut result: HashSet<C> = Default::default();
             for tab in &value {
                 for card in tab {
-                    assert!(result.insert(*card));
+                    if !card.info().is_card() {
+                        return Err(ParseError::NotACard { token: card.to_string(), at: None });
+                    }
+                    if !result.insert(*card) {
+                        return Err(ParseError::DuplicateCard { token: card.to_string(), at: None });
+                    }
                 }
             }
             result
         };
 
-        let mut fdn_cards: HashSet<C> = all_cards.difference(&tab_cards).map(|c|*c).collect();
+        let mut fdn_cards: HashSet<C> = all_cards.difference(&tab_cards).copied().collect();
 
 
         let mut result = BoardLayout {
@@ -523,49 +613,247 @@ impl From<[Vec<C>; 11]> for BoardLayout {
             }
         }
 
-        assert!(fdn_cards.len() <= 1, "{} cards left: {:?}", fdn_cards.len(), fdn_cards);
+        if fdn_cards.len() > 1 {
+            let mut missing: Vec<C> = fdn_cards.into_iter().collect();
+            missing.sort();
+            return Err(ParseError::ImpossibleFoundation { missing });
+        }
 
         result.info.freecell = fdn_cards.drain().next().unwrap_or(C::FREECELL);
 
-        result
+        Ok(result)
     }
 }
 
-use std::convert::TryInto;
+impl FromStr for BoardLayout {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seen: HashSet<C> = Default::default();
 
-fn vec_to_arr<T, const N: usize>(v: Vec<T>) -> Result<[T; N],()> {
-    v.try_into().or(Err(()))
+        // lines starting with '#' are comments, such as the header written by the alternate format
+        let tab_vec = s.lines().enumerate().filter(|(_, line)| !line.starts_with('#')).map(|(l, line)|{
+            line.split_whitespace().map(|cardstr|{
+                // split_whitespace hands back subslices, so the token's offset gives its column
+                let pos = Pos { line: l + 1, column: cardstr.as_ptr() as usize - line.as_ptr() as usize + 1 };
+                let card: C = cardstr.parse().map_err(|e: ParseError| e.at(pos))?;
+                if !card.info().is_card() {
+                    return Err(ParseError::NotACard { token: cardstr.to_string(), at: Some(pos) });
+                }
+                if !seen.insert(card) {
+                    return Err(ParseError::DuplicateCard { token: cardstr.to_string(), at: Some(pos) });
+                }
+                Ok(card)
+            }).collect::<Result<Vec<C>, ParseError>>()
+        }).collect::<Result<Vec<Vec<C>>, ParseError>>()?;
+
+        let rows = tab_vec.len();
+        let tab: [Vec<C>; 11] = tab_vec.try_into().map_err(|_| ParseError::RowCount { rows })?;
+
+        tab.try_into()
+    }
 }
 
-impl FromStr for BoardLayout {
-    type Err = ();
+impl fmt::Display for BoardLayout {
+    /// One line per tableau column, listed bottom-to-top, in the same format [`FromStr`] reads.
+    /// The alternate form adds a comment line up front showing the foundations and freecell.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "# foundation:")?;
+            for top in &self.info.foundation {
+                write!(f, " {}", top)?;
+            }
+            writeln!(f, " {} freecell: {}", self.info.down_foundn, self.info.freecell)?;
+        }
+        for tab in &self.tableau {
+            let cards: Vec<String> = tab.iter().map(|card| card.to_string()).collect();
+            writeln!(f, "{}", cards.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+
+impl fmt::Display for MoveLoc {
+    /// The slot as the player sees it, with columns numbered from 1.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveLoc::Tableau(i) => write!(f, "column {}", i + 1),
+            MoveLoc::Freecell => write!(f, "freecell"),
+        }
+    }
+}
+
+/// A player move described by the cards involved rather than the slots, as a person playing would follow it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// The card to pick up.
+    pub card: C,
+    /// How many cards move together: `card` and those stacked on it.
+    pub count: usize,
+    /// The card to put it on, or [`C::TABLEAU`] or [`C::FREECELL`] for an empty column or the freecell.
+    pub onto: C,
+    /// Cards that score on their own once the move is made, in order.
+    pub scored: Vec<C>,
+}
+
+/// Describe each move of a solution in terms of cards, replaying it from `board`.
+pub fn instructions(mut board: Board, moves: &[Move]) -> Vec<Instruction> {
+    board.apply_forced();
+    moves.iter().map(|mv| {
+        let card = board.top(mv.src);
+        let onto = board.top(mv.dst);
+        let undo = board.apply_move(*mv);
+        Instruction { card, count: 1, onto, scored: undo.scored().collect() }
+    }).collect()
+}
+
+/// Describe each move of a solution made of run moves, such as one from [`compress`](crate::supermove::compress),
+/// replaying it from `board`, or `None` if one of them can't be made.
+pub fn run_instructions(mut board: Board, moves: &[StackMove]) -> Option<Vec<Instruction>> {
+    board.apply_forced();
+    moves.iter().map(|sm| {
+        let mut card = board.top(sm.src);
+        for _ in 1..sm.count {
+            card = board.state.cards[card.0 as usize];
+        }
+        let onto = board.top(sm.dst);
+        let mut scored = Vec::new();
+        for mv in expand(&board, *sm)? {
+            scored.extend(board.apply_move(mv).scored());
+        }
+        Some(Instruction { card, count: sm.count as usize, onto, scored })
+    }).collect()
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.count {
+            1 => write!(f, "Move {:#}", self.card)?,
+            2 => write!(f, "Move {:#} and the card on it", self.card)?,
+            n => write!(f, "Move {:#} and the {} cards on it", self.card, n - 1)?,
+        }
+        match self.onto {
+            C::TABLEAU => write!(f, " to an empty column")?,
+            C::FREECELL => write!(f, " to the free cell")?,
+            onto => write!(f, " onto {:#}", onto)?,
+        }
+        if let Some((first, rest)) = self.scored.split_first() {
+            write!(f, ", scoring {:#}", first)?;
+            for card in rest {
+                write!(f, " {:#}", card)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A player move written by the cards involved: the card to pick up, then what to put it on,
+/// as in `7* > 8*`, `9| > FF` for the freecell or `J! > --` for an empty column.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct CardMove {
+    /// The card to pick up.
+    pub card: C,
+    /// The card to put it on, or [`C::TABLEAU`] or [`C::FREECELL`] for an empty column or the freecell.
+    pub onto: C,
+}
+
+impl fmt::Display for CardMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#} > {:#}", self.card, self.onto)
+    }
+}
+
+impl CardMove {
+    /// Parse a move, with errors pointing into the file when it's line `line` of one.
+    fn parse_line(s: &str, line: Option<usize>) -> Result<Self, ParseError> {
+        // tokens are subslices of `s`, so their offset gives the column
+        let locate = |e: ParseError, token: &str| match line {
+            Some(line) => e.at(Pos { line, column: token.as_ptr() as usize - s.as_ptr() as usize + 1 }),
+            None => e,
+        };
+        let (card, onto) = s.split_once('>')
+            .map(|(card, onto)| (card.trim(), onto.trim()))
+            .filter(|(card, onto)| ![card, onto].iter().any(|t| t.is_empty() || t.contains(char::is_whitespace)))
+            .ok_or_else(|| locate(ParseError::BadMove { token: s.trim().to_string(), at: None }, s.trim()))?;
+
+        let card_c: C = card.parse().map_err(|e| locate(e, card))?;
+        if !card_c.info().is_card() {
+            return Err(locate(ParseError::NotACard { token: card.to_string(), at: None }, card));
+        }
+        let onto_c: C = onto.parse().map_err(|e| locate(e, onto))?;
+        if !(onto_c.info().is_card() || onto_c == C::TABLEAU || onto_c == C::FREECELL) {
+            return Err(locate(ParseError::BadMove { token: onto.to_string(), at: None }, onto));
+        }
+        Ok(CardMove { card: card_c, onto: onto_c })
+    }
+}
+
+impl FromStr for CardMove {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut is_err = false;
+        Self::parse_line(s, None)
+    }
+}
 
-        let tab_vec = s.lines().map(|line|{
-            line.split_whitespace().map(|cardstr|{
-                cardstr.parse().unwrap_or_else(|_| {is_err = true; C::NO_CARD})
-            }).collect::<Vec<C>>()
-        }).collect::<Vec<Vec<C>>>();
+impl From<&Instruction> for CardMove {
+    fn from(step: &Instruction) -> Self {
+        debug_assert_eq!(step.count, 1, "move notation only covers single cards");
+        CardMove { card: step.card, onto: step.onto }
+    }
+}
 
-        if is_err { return Err(()) }
+/// A solution in move notation, one [`CardMove`] per line, for saving, diffing and sharing.
+/// Blank lines and lines starting with `#` are skipped when parsing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript(pub Vec<CardMove>);
 
-        let tab: [Vec<C>; 11]  = vec_to_arr::<Vec<C>, 11>(tab_vec)?;
+impl Transcript {
+    /// Write down a solution given as slot moves, replaying it from `board`.
+    pub fn record(board: Board, moves: &[Move]) -> Self {
+        Transcript(instructions(board, moves).iter().map(CardMove::from).collect())
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = ParseError;
 
-        return Ok(tab.into())
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(l, line)| CardMove::parse_line(line, Some(l + 1)))
+            .collect::<Result<_, _>>()
+            .map(Transcript)
     }
 }
 
+impl fmt::Display for Transcript {
+    /// One move per line, each terminated by a newline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for mv in &self.0 {
+            writeln!(f, "{}", mv)?;
+        }
+        Ok(())
+    }
+}
 
+
+/// Layouts the tests of several modules play from, and the helpers to set them up. The ones the command-line tests
+/// play too are files under `tests/fixtures`, read here as they are there.
 #[cfg(test)]
-mod board_tests {
+pub(crate) mod fixtures {
+    use super::BoardLayout;
+    use crate::state::Board;
 
-    use super::*;
+    /// Two kings, each on the other suit's queen, so putting either one in an empty column wins.
+    pub const TWO_KINGS: &str = include_str!("../tests/fixtures/two_kings.deal");
 
-    #[test]
-    fn parse_fresh() {
-        let s = "\
+    /// Only the queens and kings of two suits left, each king buried under the other suit's queen.
+    pub const NEARLY_WON: &str = include_str!("../tests/fixtures/nearly_won.deal");
+
+    /// A deal straight from the shuffle, with nothing scored.
+    pub const FRESH: &str = "\
 05 07 2$ 8| T! 3* 8$
 18 20 9* 03 K| 2! 01
 16 09 9| 4* 3! 15 11
@@ -578,6 +866,58 @@ Q* 6$ J! 14 5! 00 8!
 J| 21 7$ Q$ J* 6! 9!
 9$ 17 K! J$ Q! 8* 08";
 
+    /// No empty column, an occupied freecell, and no two tops that stack.
+    pub const STUCK: &str = "\
+J| Q| K|
+J* Q* K*
+J! Q! K!
+J$ Q$ K$
+02 04
+03 06
+05 08
+07 10
+09 12
+11 13 14
+15 17 19 16";
+
+    /// Runs of wands to move together. Wands up to 6| are scored, so with 7| buried nothing else can score.
+    pub const RUNS: &str = "8| J| T| 9|\n7| Q|\nK|\n\n\n\n\n\n\n\n\n";
+
+    /// A few major arcana, with 05 stuck between 03 and 09.
+    pub const MAJORS: &str = "03 09 05\n06 04 07 08\n\n\n\n\n\n\n\n\n\n";
+
+    /// 07 alone in the first column and eight empty columns; the foundations reach 04 and 12 so nothing scores.
+    pub const OPEN_COLUMNS: &str = "07\n05 09\n06 11 08 10\n\n\n\n\n\n\n\n\n";
+
+    /// Every column empty, as in a won game.
+    pub const CLEARED: &str = "\n\n\n\n\n\n\n\n\n\n\n";
+
+    pub fn layout(s: &str) -> BoardLayout {
+        s.parse().unwrap()
+    }
+
+    pub fn board(s: &str) -> Board {
+        layout(s).into()
+    }
+
+    /// [`board`], with its forced moves made.
+    pub fn settled(s: &str) -> Board {
+        let mut board = board(s);
+        board.apply_forced();
+        board
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+
+    use super::*;
+    use super::fixtures::*;
+
+    #[test]
+    fn parse_fresh() {
+        let s = FRESH;
+
         let fdn = [C::NO_CARD; 5];
         let dfdn =  C::NO_CARD;
         let frec =  C::FREECELL;
@@ -644,19 +984,119 @@ J| 21 7$ Q$ J* 6! 9!
         assert_eq!(bl.info.freecell, frec);
     }
     #[test]
-    fn parse_empty() {
-        let s = "\
+    fn display_roundtrip() {
+        let fixtures = [FRESH, TWO_KINGS, CLEARED, MAJORS];
+        for s in fixtures {
+            let bl: BoardLayout = s.parse().expect("parse error");
+            assert_eq!(bl.to_string().parse::<BoardLayout>(), Ok(bl.clone()));
+            assert_eq!(format!("{:#}", bl).parse::<BoardLayout>(), Ok(bl.clone()));
+            assert_eq!(bl.to_string().lines().count(), 11);
+        }
+    }
 
+    #[test]
+    fn layout_from_board() {
+        let bl = layout(TWO_KINGS);
+        let board: Board = bl.clone().into();
+        assert_eq!(BoardLayout::from(board), bl);
 
+        let canon = BoardLayout::from(board.state);
+        assert_eq!(Board::from(canon).state, board.state);
+    }
 
+    #[test]
+    fn describe_moves() {
+        let board = board(TWO_KINGS);
+        let moves = [
+            Move { src: MoveLoc::Tableau(0), dst: MoveLoc::Freecell },
+            Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(2) },
+        ];
+        let lines: Vec<String> = instructions(board, &moves).iter().map(|i| i.to_string()).collect();
+        assert_eq!(lines, ["Move K* to the free cell", "Move K* to an empty column, scoring Q| K| Q* K*"]);
 
+        let onto = Instruction { card: "7*".parse().unwrap(), count: 1, onto: "8*".parse().unwrap(), scored: vec![] };
+        assert_eq!(onto.to_string(), "Move 7* onto 8*");
+        let run = Instruction { count: 3, ..onto };
+        assert_eq!(run.to_string(), "Move 7* and the 2 cards on it onto 8*");
+    }
 
+    #[test]
+    fn describe_runs() {
+        let board = board(RUNS);
+        let moves = [
+            StackMove { src: MoveLoc::Tableau(0), dst: MoveLoc::Tableau(1), count: 3 },
+            StackMove { src: MoveLoc::Tableau(1), dst: MoveLoc::Tableau(3), count: 1 },
+        ];
+        let lines: Vec<String> = run_instructions(board, &moves).unwrap().iter().map(|i| i.to_string()).collect();
+        assert_eq!(lines, ["Move J| and the 2 cards on it onto Q|", "Move 9| to an empty column"]);
 
+        // the third column is a lone king, not a run of two
+        let too_long = StackMove { src: MoveLoc::Tableau(2), dst: MoveLoc::Tableau(3), count: 2 };
+        assert_eq!(run_instructions(board, &[moves[0], too_long]), None);
+    }
 
+    #[test]
+    fn move_notation() {
+        let c = |s: &str| s.parse::<C>().unwrap();
+        assert_eq!("7* > 8*".parse(), Ok(CardMove { card: c("7*"), onto: c("8*") }));
+        assert_eq!("9|>FF".parse(), Ok(CardMove { card: c("9|"), onto: C::FREECELL }));
+        assert_eq!("  J! > -- ".parse(), Ok(CardMove { card: c("J!"), onto: C::TABLEAU }));
+        assert_eq!(CardMove { card: c("7*"), onto: c("8*") }.to_string(), "7* > 8*");
+        assert_eq!(CardMove { card: c("J!"), onto: C::TABLEAU }.to_string(), "J! > --");
+        assert_eq!(CardMove { card: c("12"), onto: C::FREECELL }.to_string(), "12 > FF");
+
+        use ParseError::*;
+        assert_eq!("7* 8*".parse::<CardMove>(), Err(BadMove { token: "7* 8*".into(), at: None }));
+        assert_eq!("7* > vv".parse::<CardMove>(), Err(BadMove { token: "vv".into(), at: None }));
+        assert_eq!("FF > 8*".parse::<CardMove>(), Err(NotACard { token: "FF".into(), at: None }));
+        let msg = "7* 8*".parse::<CardMove>().unwrap_err().to_string();
+        assert!(msg.ends_with("is not a move like \"7* > 8*\", \"9| > FF\" or \"J! > --\""), "{}", msg);
+
+        let err = "# saved\n7* > 8*\n\n9| > 8*|\n".parse::<Transcript>().unwrap_err();
+        assert_eq!(err, AmbiguousSuit { token: "8*|".into(), at: Some(Pos { line: 4, column: 6 }) });
+    }
 
+    #[test]
+    fn transcript_roundtrip() {
+        let board = board(TWO_KINGS);
+        let moves = [
+            Move { src: MoveLoc::Tableau(0), dst: MoveLoc::Freecell },
+            Move { src: MoveLoc::Freecell, dst: MoveLoc::Tableau(2) },
+        ];
+        let transcript = Transcript::record(board, &moves);
+        assert_eq!(transcript.to_string(), "K* > FF\nK* > --\n");
+        assert_eq!(transcript.to_string().parse(), Ok(transcript.clone()));
+        assert_eq!(crate::verify::verify_transcript(&layout(TWO_KINGS), &transcript), Ok(()));
+    }
 
+    #[test]
+    fn parse_errors() {
+        use ParseError::*;
+        let pos = |line, column| Some(Pos { line, column });
+
+        assert_eq!("7*|".parse::<C>(), Err(AmbiguousSuit { token: "7*|".into(), at: None }));
+        assert_eq!("*".parse::<C>(), Err(BadRank { token: "*".into(), at: None }));
+        assert_eq!("15|".parse::<C>(), Err(RankOutOfRange { token: "15|".into(), at: None }));
+        assert_eq!("A*".parse::<C>(), Err(RankOutOfRange { token: "A*".into(), at: None }));
+        assert_eq!("22".parse::<C>(), Err(RankOutOfRange { token: "22".into(), at: None }));
+
+        let rows = |s: &str| format!("{}{}", s, "\n".repeat(12 - s.lines().count()));
+        assert_eq!(rows("Q| K*\nQ* 8*|").parse::<BoardLayout>(), Err(AmbiguousSuit { token: "8*|".into(), at: pos(2, 4) }));
+        assert_eq!(rows("Q| K*\n  Q* Q|").parse::<BoardLayout>(), Err(DuplicateCard { token: "Q|".into(), at: pos(2, 6) }));
+        assert_eq!(rows("Q| K*\nQ* FF").parse::<BoardLayout>(), Err(NotACard { token: "FF".into(), at: pos(2, 4) }));
+        assert_eq!("Q| K*\nQ* K|".parse::<BoardLayout>(), Err(RowCount { rows: 2 }));
+
+        // with J| still out, Q| and K| can't both be the one freecell card
+        let missing = ["Q|", "K|"].map(|c| c.parse::<C>().unwrap()).to_vec();
+        assert_eq!(rows("J|").parse::<BoardLayout>(), Err(ImpossibleFoundation { missing }));
+
+        let msg = rows("Q| K*\nQ* 8*|").parse::<BoardLayout>().unwrap_err().to_string();
+        assert_eq!(msg, "line 2, column 4: \"8*|\" names more than one suit");
+    }
 
-";
+    #[test]
+    fn parse_empty() {
+        let s = CLEARED;
 
         let fdn = [
             "K|".parse().unwrap(),
@@ -666,7 +1106,7 @@ J| 21 7$ Q$ J* 6! 9!
             "21".parse().unwrap(),
         ];
         let dfdn = C::NO_CARD;
-        let frec = C::NO_CARD;
+        let frec = C::FREECELL;
 
         let bl: BoardLayout = s.parse().expect("parse error");
         assert_eq!(bl.info.foundation, fdn);
@@ -0,0 +1,314 @@
+//! Replaying a solution against a deal with its own, deliberately simple, model of the rules.
+//! 
+//! Nothing here uses [`Board`](crate::state::Board) or the solvers' move machinery, so a solution checked here
+//! doesn't depend on the code that produced it being right.
+use std::fmt;
+use crate::state::*;
+use crate::text::{BoardLayout, CardMove, Transcript};
+
+/// Why a move can't be made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Illegal {
+    /// There's no card at the source.
+    EmptySource,
+    /// The source and destination are the same place.
+    SameSlot,
+    /// The freecell already holds a card.
+    FreecellFull,
+    /// The card isn't the same suit and one rank away from the card it would go on.
+    DoesNotStack { card: C, onto: C },
+    /// The card named to move isn't on top of a column or in the freecell.
+    NotFree(C),
+    /// The card named to move onto isn't on top of a column.
+    Covered(C),
+    /// The move is to an empty column, and there isn't one.
+    NoEmptyColumn,
+}
+
+impl fmt::Display for Illegal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptySource => write!(f, "nothing to move"),
+            Self::SameSlot => write!(f, "source and destination are the same"),
+            Self::FreecellFull => write!(f, "the freecell is occupied"),
+            Self::DoesNotStack { card, onto } => write!(f, "{:#} doesn't stack on {:#}", card, onto),
+            Self::NotFree(card) => write!(f, "{:#} isn't free to move", card),
+            Self::Covered(onto) => write!(f, "{:#} isn't on top of a column", onto),
+            Self::NoEmptyColumn => write!(f, "there is no empty column"),
+        }
+    }
+}
+
+/// How a solution failed to check out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// Move number `index` (from zero) can't be made from the position shown.
+    Illegal { index: usize, mv: Move, why: Illegal, layout: Box<BoardLayout> },
+    /// Move number `index` (from zero) of a transcript can't be made from the position shown.
+    Unplayable { index: usize, mv: CardMove, why: Illegal, layout: Box<BoardLayout> },
+    /// Every move was legal, but the position shown still has cards left to score.
+    NotWon { layout: Box<BoardLayout> },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Illegal { index, mv, why, layout } => {
+                write!(f, "move {} ({} to {}) is illegal: {}\n{:#}", index + 1, mv.src, mv.dst, why, layout)
+            },
+            Self::Unplayable { index, mv, why, layout } => {
+                write!(f, "move {} ({}) is illegal: {}\n{:#}", index + 1, mv, why, layout)
+            },
+            Self::NotWon { layout } => write!(f, "the moves don't finish the game:\n{:#}", layout),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// The position being replayed, as the player sees it.
+struct Table {
+    tableau: [Vec<C>; 11],
+    freecell: Option<C>,
+    /// Next rank each foundation needs, by suit, with the major arcana's ascending foundation last.
+    up_next: [u8; 5],
+    /// Next rank the descending major arcana foundation needs.
+    down_next: u8,
+}
+
+impl Table {
+    fn new(start: &BoardLayout) -> Self {
+        let next = |top: C, first: u8| {
+            let ti = top.info();
+            if ti.is_card() { ti.card_rank() + 1 } else { first }
+        };
+        let f = &start.info.foundation;
+        let di = start.info.down_foundn.info();
+        Self {
+            tableau: start.tableau.clone(),
+            freecell: Some(start.info.freecell).filter(|c| c.info().is_card()),
+            up_next: [next(f[0], 2), next(f[1], 2), next(f[2], 2), next(f[3], 2), next(f[4], 0)],
+            down_next: if di.is_card() { di.card_rank() - 1 } else { 21 },
+        }
+    }
+
+    /// Score `card` if a foundation will take it, returning whether it did.
+    fn score(&mut self, card: C) -> bool {
+        let ci = card.info();
+        let (suit, rank) = (ci.card_suit(), ci.card_rank());
+        if suit.is_minor() {
+            // the freecell sits over the minor foundations while it holds a card
+            if self.freecell.is_some() || self.up_next[suit as usize] != rank { return false }
+            self.up_next[suit as usize] += 1;
+        } else if self.up_next[suit as usize] == rank && rank <= self.down_next {
+            self.up_next[suit as usize] += 1;
+        } else if self.down_next == rank && rank >= self.up_next[suit as usize] {
+            self.down_next = self.down_next.wrapping_sub(1);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Keep scoring exposed cards until none will go.
+    fn auto_score(&mut self) {
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for i in 0..self.tableau.len() {
+                while let Some(&card) = self.tableau[i].last() {
+                    if !self.score(card) { break }
+                    self.tableau[i].pop();
+                    progress = true;
+                }
+            }
+            // a minor card in the freecell is itself covering its foundation, so only majors leave from here
+            if let Some(card) = self.freecell {
+                if self.score(card) {
+                    self.freecell = None;
+                    progress = true;
+                }
+            }
+        }
+    }
+
+    fn top(&self, loc: MoveLoc) -> Option<C> {
+        match loc {
+            MoveLoc::Tableau(i) => self.tableau[i as usize].last().copied(),
+            MoveLoc::Freecell => self.freecell,
+        }
+    }
+
+    /// The slots `mv` moves between here, without checking that the card may go there. A move to `--` goes to the
+    /// leftmost empty column, since any empty column plays the same.
+    fn locate(&self, mv: CardMove) -> Result<Move, Illegal> {
+        let slot = |card: C| MoveLoc::all().find(|&loc| self.top(loc) == Some(card));
+        let src = slot(mv.card).ok_or(Illegal::NotFree(mv.card))?;
+        let dst = match mv.onto {
+            C::FREECELL => MoveLoc::Freecell,
+            C::TABLEAU => (0..11u8).map(MoveLoc::Tableau).find(|&loc| self.top(loc).is_none()).ok_or(Illegal::NoEmptyColumn)?,
+            onto => slot(onto).filter(|&loc| loc != MoveLoc::Freecell).ok_or(Illegal::Covered(onto))?,
+        };
+        Ok(Move { src, dst })
+    }
+
+    fn make(&mut self, mv: Move) -> Result<(), Illegal> {
+        if mv.src == mv.dst { return Err(Illegal::SameSlot) }
+        let card = self.top(mv.src).ok_or(Illegal::EmptySource)?;
+        match mv.dst {
+            MoveLoc::Freecell => if self.freecell.is_some() { return Err(Illegal::FreecellFull) },
+            MoveLoc::Tableau(i) => if let Some(&onto) = self.tableau[i as usize].last() {
+                let (ci, oi) = (card.info(), onto.info());
+                if ci.card_suit() != oi.card_suit() || ci.card_rank().abs_diff(oi.card_rank()) != 1 {
+                    return Err(Illegal::DoesNotStack { card, onto });
+                }
+            },
+        }
+
+        match mv.src {
+            MoveLoc::Tableau(i) => { self.tableau[i as usize].pop(); },
+            MoveLoc::Freecell => self.freecell = None,
+        }
+        match mv.dst {
+            MoveLoc::Tableau(i) => self.tableau[i as usize].push(card),
+            MoveLoc::Freecell => self.freecell = Some(card),
+        }
+        self.auto_score();
+        Ok(())
+    }
+
+    fn is_won(&self) -> bool {
+        self.freecell.is_none() && self.tableau.iter().all(|t| t.is_empty())
+    }
+
+    fn layout(&self) -> BoardLayout {
+        let top = |suit: usize, next: u8, first: u8| {
+            if next == first { C::NO_CARD } else {
+                let s = [Suit::Wands, Suit::Stars, Suit::Swrds, Suit::Cuups, Suit::Magic][suit];
+                CardInfo::Card(s, next - 1).pack()
+            }
+        };
+        let mut info = BoardInfo {
+            tableau: [C::TABLEAU; 11],
+            freecell: self.freecell.unwrap_or(C::FREECELL),
+            foundation: [top(0, self.up_next[0], 2), top(1, self.up_next[1], 2), top(2, self.up_next[2], 2),
+                top(3, self.up_next[3], 2), top(4, self.up_next[4], 0)],
+            down_foundn: if self.down_next == 21 { C::NO_CARD } else { CardInfo::Card(Suit::Magic, self.down_next + 1).pack() },
+        };
+        for (top, tab) in info.tableau.iter_mut().zip(&self.tableau) {
+            *top = tab.last().copied().unwrap_or(C::TABLEAU);
+        }
+        BoardLayout { tableau: self.tableau.clone(), info }
+    }
+}
+
+/// Replay `moves` from `start`, checking that each is legal and that together they win the game.
+pub fn verify(start: &BoardLayout, moves: &[Move]) -> Result<(), VerifyError> {
+    let mut table = Table::new(start);
+    table.auto_score();
+
+    for (index, mv) in moves.iter().enumerate() {
+        let layout = Box::new(table.layout());
+        if let Err(why) = table.make(*mv) {
+            return Err(VerifyError::Illegal { index, mv: *mv, why, layout });
+        }
+    }
+
+    if table.is_won() { Ok(()) } else { Err(VerifyError::NotWon { layout: Box::new(table.layout()) }) }
+}
+
+/// Like [`verify`], for moves written as cards: each is found on the table as it stands when it's reached.
+pub fn verify_transcript(start: &BoardLayout, transcript: &Transcript) -> Result<(), VerifyError> {
+    let mut table = Table::new(start);
+    table.auto_score();
+
+    for (index, mv) in transcript.0.iter().enumerate() {
+        let layout = Box::new(table.layout());
+        if let Err(why) = table.locate(*mv).and_then(|slots| table.make(slots)) {
+            return Err(VerifyError::Unplayable { index, mv: *mv, why, layout });
+        }
+    }
+
+    if table.is_won() { Ok(()) } else { Err(VerifyError::NotWon { layout: Box::new(table.layout()) }) }
+}
+
+
+#[cfg(test)]
+mod verify_tests {
+    use super::*;
+    use crate::deal::deal;
+    use crate::solve::solve_best_first;
+    use crate::solve::SolveResult;
+    use crate::text::fixtures::*;
+
+    fn mv(src: MoveLoc, dst: MoveLoc) -> Move {
+        Move { src, dst }
+    }
+
+    #[test]
+    fn verify_solver_solutions() {
+        // deals the best-first search solves quickly
+        for seed in [9, 10] {
+            let layout = deal(seed);
+            let SolveResult::Solved(moves) = solve_best_first(layout.clone().into()) else { panic!("seed {}", seed) };
+            assert_eq!(verify(&layout, &moves), Ok(()), "seed {}", seed);
+            assert!(matches!(verify(&layout, &moves[..moves.len() - 1]), Err(VerifyError::NotWon { .. })));
+        }
+    }
+
+    #[test]
+    fn verify_reports_illegal() {
+        let layout = layout(TWO_KINGS);
+        use MoveLoc::*;
+
+        let err = verify(&layout, &[mv(Tableau(3), Freecell)]).unwrap_err();
+        assert!(matches!(err, VerifyError::Illegal { index: 0, why: Illegal::EmptySource, .. }));
+
+        let err = verify(&layout, &[mv(Tableau(0), Freecell), mv(Tableau(1), Freecell)]).unwrap_err();
+        let VerifyError::Illegal { index: 1, why: Illegal::FreecellFull, layout: at, .. } = err else { panic!("{:?}", err) };
+        assert_eq!(at.info.freecell, "K*".parse().unwrap());
+        assert_eq!(at.tableau[0], vec!["Q|".parse().unwrap()]);
+
+        let err = verify(&layout, &[mv(Tableau(0), Tableau(1))]).unwrap_err();
+        let k = |s: &str| s.parse::<C>().unwrap();
+        assert!(matches!(err, VerifyError::Illegal { why: Illegal::DoesNotStack { card, onto }, .. } if card == k("K*") && onto == k("K|")));
+        assert!(err.to_string().starts_with("move 1 (column 1 to column 2) is illegal: "));
+    }
+
+    #[test]
+    fn verify_transcript_finds_cards() {
+        let layout = layout(TWO_KINGS);
+        let check = |moves: &str| verify_transcript(&layout, &moves.parse().unwrap());
+
+        assert_eq!(check("K* > --"), Ok(()));
+        assert_eq!(check("K* > FF\nK* > --"), Ok(()));
+        assert!(matches!(check("K* > FF"), Err(VerifyError::NotWon { .. })));
+
+        let unplayable = |moves: &str| match check(moves) {
+            Err(VerifyError::Unplayable { index, why, .. }) => (index, why),
+            other => panic!("{}: {:?}", moves, other),
+        };
+        let k = |s: &str| s.parse::<C>().unwrap();
+        assert_eq!(unplayable("K* > K|"), (0, Illegal::DoesNotStack { card: k("K*"), onto: k("K|") }));
+        assert_eq!(unplayable("Q| > FF"), (0, Illegal::NotFree(k("Q|"))));
+        assert_eq!(unplayable("K* > FF\nK| > K*"), (1, Illegal::Covered(k("K*"))));
+        assert_eq!(unplayable("K* > FF\nK| > FF"), (1, Illegal::FreecellFull));
+        assert_eq!(unplayable("K* > K*"), (0, Illegal::SameSlot));
+
+        let err = check("K* > K|").unwrap_err().to_string();
+        assert!(err.starts_with("move 1 (K* > K|) is illegal: K* doesn't stack on K|\n"), "{}", err);
+    }
+
+    #[test]
+    fn verify_freecell_blocks_minors() {
+        let layout = layout(TWO_KINGS);
+        use MoveLoc::*;
+
+        // with K* parked in the freecell nothing can score, so taking it out is needed to finish
+        let parked = [mv(Tableau(0), Freecell)];
+        let VerifyError::NotWon { layout: at } = verify(&layout, &parked).unwrap_err() else { panic!() };
+        assert_eq!(at.tableau[0], vec!["Q|".parse().unwrap()]);
+        assert_eq!(verify(&layout, &[mv(Tableau(0), Freecell), mv(Freecell, Tableau(5))]), Ok(()));
+        assert_eq!(verify(&layout, &[mv(Tableau(0), Tableau(5))]), Ok(()));
+    }
+}
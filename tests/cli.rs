@@ -0,0 +1,63 @@
+use std::{fs, path::PathBuf, process::{Command, Output}};
+
+/// Write `contents` to a scratch file unique to this test run.
+fn scratch(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fortune2-cli-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn fortune2(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fortune2")).args(args).output().unwrap()
+}
+
+/// Deals the library's tests play as well.
+const TWO_KINGS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/two_kings.deal");
+const NEARLY_WON: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/nearly_won.deal");
+
+#[test]
+fn verify_accepts_a_win() {
+    let moves = scratch("win.moves", "# park the king, then give it a column\nK* > FF\nK* > --\n");
+    let out = fortune2(&["verify", TWO_KINGS, moves.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "verified: 2 moves win the game\n");
+}
+
+#[test]
+fn verify_shows_the_board_at_an_illegal_move() {
+    let moves = scratch("illegal.moves", "K* > K|\n");
+    let out = fortune2(&["verify", TWO_KINGS, moves.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(4));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.starts_with("rejected: move 1 (K* > K|) is illegal: K* doesn't stack on K|\n# foundation:"), "{}", stdout);
+    assert!(stdout.contains("\n|Q *K\n*Q |K\n"), "{}", stdout);
+}
+
+#[test]
+fn unknown_options_are_usage_errors() {
+    let out = fortune2(&["solve", "--bogus", "-"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("error: unknown option --bogus\nusage:"));
+}
+
+#[test]
+fn ida_needs_a_limit() {
+    let out = fortune2(&["solve", "--mode", "ida", TWO_KINGS]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "error: --mode ida needs --max-nodes or --timeout\n");
+
+    let out = fortune2(&["solve", "--mode", "ida", "--max-nodes", "1000", TWO_KINGS]);
+    assert_eq!(out.status.code(), Some(0));
+}
+
+#[test]
+fn optimal_says_so() {
+    // K* off Q| first, then two moves each to dig out J! and J$
+    let out = fortune2(&["solve", "--mode", "optimal", NEARLY_WON]);
+    assert_eq!(out.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.ends_with("\noptimal: 5 moves\n"), "{}", stdout);
+
+    let out = fortune2(&["solve", NEARLY_WON]);
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("optimal"));
+}